@@ -0,0 +1,174 @@
+//! Throughput benchmark for `DiagnosticsEngine::analyze`.
+//!
+//! Run with `cargo bench`. Builds a synthetic corpus of handler files and
+//! reports the time to lint it, alone and as the modules of one crate, then
+//! compares matching every line against each rule pattern in turn with
+//! matching it once through a `RegexSet`.
+
+use std::hint::black_box;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use omnia_lint::{CrateIndex, DiagnosticsEngine, ProviderCatalog, RuleScope, RuleSet};
+use regex::RegexSet;
+
+/// Number of handler files in the corpus.
+const FILES: usize = 200;
+
+/// Number of modules in the synthetic crate.
+const MODULES: usize = 500;
+
+/// Number of timed iterations per measurement.
+const ITERATIONS: usize = 5;
+
+fn handler_source(index: usize) -> String {
+    format!(
+        r#"use std::time::Duration;
+
+use anyhow::Context as _;
+use omnia_sdk::{{Config, Context, Handler, HttpRequest, Reply, StateStore}};
+use serde::{{Deserialize, Serialize}};
+
+/// Request for handler {index}.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Request{index} {{
+    pub id: String,
+    pub kind: String,
+    pub items: Vec<String>,
+}}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Response{index} {{
+    pub total: usize,
+    pub labels: Vec<String>,
+}}
+
+impl<P> Handler<P> for Request{index}
+where
+    P: Config + HttpRequest + StateStore,
+{{
+    type Error = omnia_sdk::Error;
+    type Input = Vec<u8>;
+    type Output = Response{index};
+
+    fn from_input(input: Self::Input) -> Result<Self, Self::Error> {{
+        serde_json::from_slice(&input).context("deserializing request")
+    }}
+
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>, Self::Error> {{
+        let endpoint = Config::get(ctx.provider, "API_ENDPOINT").await?;
+        let mut labels = Vec::with_capacity(self.items.len());
+        for item in &self.items {{
+            // Build a label for every item
+            let label = format!("{{}}-{{}}", self.id, item);
+            labels.push(label.clone());
+        }}
+
+        match self.kind.as_str() {{
+            "fast" => {{}}
+            "slow" => {{}}
+            _ => {{}}
+        }}
+
+        let key = format!("handler-{index}-{{}}", self.id);
+        let value = serde_json::to_vec(&labels).context("serializing labels")?;
+        StateStore::set(ctx.provider, &key, &value, Some(Duration::from_secs(3600))).await?;
+        let raw = ctx.provider.get(&endpoint).await.unwrap();
+        println!("fetched {{}} bytes", raw.len());
+
+        Ok(Reply::ok(Response{index} {{ total: labels.len(), labels }}))
+    }}
+}}
+"#
+    )
+}
+
+/// Best-of-`ITERATIONS` wall time of `f`.
+fn time(mut f: impl FnMut()) -> Duration {
+    (0..ITERATIONS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn main() {
+    let corpus: Vec<String> = (0..FILES).map(handler_source).collect();
+    let lines: Vec<&str> = corpus.iter().flat_map(|source| source.lines()).collect();
+    let bytes: usize = corpus.iter().map(String::len).sum();
+    println!("corpus: {} files, {} lines, {} KiB", FILES, lines.len(), bytes / 1024);
+
+    let engine = DiagnosticsEngine::new();
+    let analyze = time(|| {
+        for source in &corpus {
+            black_box(engine.analyze(source, Path::new("handler.rs")));
+        }
+    });
+    println!(
+        "analyze:            {:>10.2?} ({:.1} MiB/s)",
+        analyze,
+        bytes as f64 / analyze.as_secs_f64() / (1024.0 * 1024.0)
+    );
+
+    // Lint every module of a crate against its index, where per-file
+    // overhead that grows with the crate would make the run superlinear
+    let dir = tempfile::tempdir().expect("temporary crate directory");
+    let src = dir.path().join("src");
+    std::fs::create_dir(&src).expect("src directory");
+    let modules: Vec<String> = (0..MODULES).map(handler_source).collect();
+    let root: String = (0..MODULES).map(|index| format!("mod m{index};\n")).collect();
+    std::fs::write(src.join("lib.rs"), root).expect("crate root");
+    for (index, source) in modules.iter().enumerate() {
+        std::fs::write(src.join(format!("m{index}.rs")), source).expect("module file");
+    }
+
+    let catalog = ProviderCatalog::new();
+    let index = time(|| {
+        black_box(CrateIndex::load(&src.join("lib.rs"), &catalog));
+    });
+    let krate = CrateIndex::load(&src.join("lib.rs"), &catalog);
+    let in_crate = time(|| {
+        for (index, source) in modules.iter().enumerate() {
+            let path = src.join(format!("m{index}.rs"));
+            black_box(engine.analyze_in_crate(source, &path, Some(&krate)));
+        }
+    });
+    println!("crate: {MODULES} modules");
+    println!("crate index:        {:>10.2?}", index);
+    println!(
+        "analyze in crate:   {:>10.2?} ({:.2?} per module)",
+        in_crate,
+        in_crate / MODULES as u32
+    );
+
+    let rule_set = RuleSet::new();
+    let patterns: Vec<_> = rule_set
+        .rules
+        .iter()
+        .filter(|rule| rule.is_anti_pattern && rule.scope == RuleScope::Line)
+        .map(|rule| &rule.pattern)
+        .collect();
+    let set = RegexSet::new(patterns.iter().map(|p| p.as_str())).expect("valid rule patterns");
+
+    let sequential = time(|| {
+        for line in &lines {
+            for pattern in &patterns {
+                black_box(pattern.find(line));
+            }
+        }
+    });
+    let single_pass = time(|| {
+        for line in &lines {
+            for idx in set.matches(line).into_iter() {
+                black_box(patterns[idx].find(line));
+            }
+        }
+    });
+
+    println!("rules, one by one:  {:>10.2?}", sequential);
+    println!("rules, RegexSet:    {:>10.2?}", single_pass);
+    println!("speedup:            {:>10.1}x", sequential.as_secs_f64() / single_pass.as_secs_f64());
+}
@@ -24,13 +24,35 @@
 //!
 //! Crate-level `[lints.omnia]` tables are merged on top of
 //! `[workspace.lints.omnia]` when both exist (crate wins).
+//!
+//! In-house provider traits are declared under
+//! `[workspace.metadata.omnia.providers]` or
+//! `[package.metadata.omnia.providers]` and added to the
+//! [`ProviderCatalog`](crate::providers::ProviderCatalog):
+//!
+//! ```toml
+//! [package.metadata.omnia.providers.BlobStore]
+//! crate = "my_sdk"
+//! methods = [{ name = "put", arity = 2 }, { name = "get_blob", arity = 1 }]
+//! ```
+//!
+//! The same tables can require a reason on every `omnia::allow` directive,
+//! as the `--require-reason` flag does:
+//!
+//! ```toml
+//! [workspace.metadata.omnia]
+//! require_reason = true
+//! ```
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
+use toml::de::{DeTable, DeValue};
 
-use crate::rules::{LintLevel, RuleCategory};
+use crate::diagnostics::Diagnostic;
+use crate::providers::{ProviderMethod, ProviderTrait, Receiver};
+use crate::rules::{LintLevel, RuleCategory, RuleSeverity};
 
 /// Configuration extracted from a `Cargo.toml` `[lints.omnia]` table.
 #[derive(Debug, Clone, Default)]
@@ -47,6 +69,79 @@ pub struct CargoLintConfig {
 
     /// Per-rule severity overrides (rule ID → level).
     pub rules: HashMap<String, LintLevel>,
+
+    /// Every entry read from the `[lints.omnia]` tables, in load order.
+    ///
+    /// Kept so the resolution of a level can be explained after merging.
+    pub entries: Vec<LevelEntry>,
+
+    /// Provider traits declared under `metadata.omnia.providers`, workspace
+    /// first. Later entries replace earlier ones with the same name.
+    pub providers: Vec<ProviderTrait>,
+
+    /// `metadata.omnia.require_reason`, package over workspace.
+    pub require_reason: Option<bool>,
+}
+
+/// The `Cargo.toml` table a [`LevelEntry`] was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintTable {
+    /// `[workspace.lints.omnia]`
+    Workspace,
+    /// `[lints.omnia]`
+    Crate,
+}
+
+impl LintTable {
+    /// Return the table header as written in `Cargo.toml`.
+    pub fn header(&self) -> &'static str {
+        match self {
+            LintTable::Workspace => "[workspace.lints.omnia]",
+            LintTable::Crate => "[lints.omnia]",
+        }
+    }
+}
+
+/// A single `key = level` entry read from a `[lints.omnia]` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelEntry {
+    /// The key as written (`all`, a category key, or a rule ID).
+    pub key: String,
+
+    /// The configured level.
+    pub level: LintLevel,
+
+    /// The table the entry was read from.
+    pub table: LintTable,
+
+    /// The `Cargo.toml` the entry was read from.
+    pub source: Option<PathBuf>,
+}
+
+/// Which part of the precedence chain a [`LevelEntry`] matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelScope {
+    /// The key is the rule ID itself.
+    Rule,
+    /// The key is another ID the rule is known by.
+    Alias,
+    /// The key is the rule's category.
+    Category,
+    /// The key is the `all` supercategory.
+    All,
+}
+
+/// A candidate considered by [`CargoLintConfig::effective_level`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelCandidate<'a> {
+    /// The entry that matched.
+    pub entry: &'a LevelEntry,
+
+    /// Which precedence tier it matched in.
+    pub scope: LevelScope,
+
+    /// Whether this candidate determines the effective level.
+    pub wins: bool,
 }
 
 impl CargoLintConfig {
@@ -67,6 +162,61 @@ impl CargoLintConfig {
         self.all
     }
 
+    /// Resolve the effective [`LintLevel`] for a rule known by several ids.
+    ///
+    /// A per-rule override for any of `rule_ids` applies, earlier ids first;
+    /// otherwise precedence is the same as
+    /// [`effective_level`](Self::effective_level).
+    pub fn effective_level_any(
+        &self, rule_ids: &[&str], category: RuleCategory,
+    ) -> Option<LintLevel> {
+        if let Some(&level) = rule_ids.iter().find_map(|id| self.rules.get(*id)) {
+            return Some(level);
+        }
+        if let Some(&level) = self.categories.get(&category) {
+            return Some(level);
+        }
+        self.all
+    }
+
+    /// List every entry that could determine the level of a rule known by
+    /// `rule_ids`, in the order
+    /// [`effective_level_any`](Self::effective_level_any) considers them.
+    ///
+    /// Entries for the first id are [`LevelScope::Rule`], entries for the
+    /// others [`LevelScope::Alias`]. Within a tier, later entries (crate over
+    /// workspace) come first. The first candidate, if any, is marked as the
+    /// winner.
+    pub fn explain(&self, rule_ids: &[&str], category: RuleCategory) -> Vec<LevelCandidate<'_>> {
+        let mut tiers = Vec::new();
+        for (position, &id) in rule_ids.iter().enumerate() {
+            if !rule_ids[..position].contains(&id) {
+                let scope = if position == 0 { LevelScope::Rule } else { LevelScope::Alias };
+                tiers.push((scope, id));
+            }
+        }
+        tiers.push((LevelScope::Category, category.as_key()));
+        tiers.push((LevelScope::All, "all"));
+
+        let mut candidates: Vec<LevelCandidate<'_>> = tiers
+            .iter()
+            .flat_map(|&(scope, key)| {
+                self.entries.iter().rev().filter(move |e| e.key == key).map(move |entry| {
+                    LevelCandidate {
+                        entry,
+                        scope,
+                        wins: false,
+                    }
+                })
+            })
+            .collect();
+
+        if let Some(first) = candidates.first_mut() {
+            first.wins = true;
+        }
+        candidates
+    }
+
     /// Merge another config on top of this one (other wins on conflicts).
     pub fn merge(&mut self, other: &CargoLintConfig) {
         if other.all.is_some() {
@@ -78,6 +228,11 @@ impl CargoLintConfig {
         for (rule, &level) in &other.rules {
             self.rules.insert(rule.clone(), level);
         }
+        self.entries.extend(other.entries.iter().cloned());
+        self.providers.extend(other.providers.iter().cloned());
+        if other.require_reason.is_some() {
+            self.require_reason = other.require_reason;
+        }
     }
 
     /// Returns `true` when no overrides are configured.
@@ -136,12 +291,35 @@ pub fn load_cargo_lint_config(cargo_toml: &Path) -> Result<CargoLintConfig> {
         .and_then(|l| l.get("omnia"))
         .and_then(|q| q.as_table())
     {
-        merge_toml_table(&mut config, table);
+        merge_toml_table(&mut config, table, LintTable::Workspace);
     }
 
     // 2. lints.omnia (crate-level, wins over workspace)
     if let Some(table) = doc.get("lints").and_then(|l| l.get("omnia")).and_then(|q| q.as_table()) {
-        merge_toml_table(&mut config, table);
+        merge_toml_table(&mut config, table, LintTable::Crate);
+    }
+
+    // 3. Metadata, workspace first so the package can replace it
+    for section in ["workspace", "package"] {
+        let Some(metadata) =
+            doc.get(section).and_then(|s| s.get("metadata")).and_then(|m| m.get("omnia"))
+        else {
+            continue;
+        };
+        if let Some(table) = metadata.get("providers").and_then(|p| p.as_table()) {
+            let providers = parse_provider_table(table)
+                .with_context(|| format!("{}.metadata.omnia.providers", section))
+                .with_context(|| format!("parsing {}", cargo_toml.display()))?;
+            config.providers.extend(providers);
+        }
+        if let Some(value) = metadata.get("require_reason") {
+            let require_reason = value
+                .as_bool()
+                .context("`require_reason` must be a boolean")
+                .with_context(|| format!("{}.metadata.omnia", section))
+                .with_context(|| format!("parsing {}", cargo_toml.display()))?;
+            config.require_reason = Some(require_reason);
+        }
     }
 
     Ok(config)
@@ -158,6 +336,184 @@ pub fn discover_config(path: &Path) -> Result<CargoLintConfig> {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Validation
+// ---------------------------------------------------------------------------
+
+/// Valid level strings, used for "did you mean" suggestions.
+const LEVEL_NAMES: &[&str] = &["allow", "warn", "deny", "forbid"];
+
+/// Validate the `[lints.omnia]` tables of a `Cargo.toml`.
+///
+/// Every key must be `all`, a category key, or one of `known_rules`, and
+/// every value must be a valid level. Offending entries are reported as
+/// `unknown_lint` and `invalid_lint_level` diagnostics pointing at the TOML
+/// line, with a suggestion when a close match exists.
+pub fn validate_lint_tables(content: &str, known_rules: &[String]) -> Vec<Diagnostic> {
+    let Ok(doc) = DeTable::parse(content) else {
+        // Syntax errors are reported when the config is loaded.
+        return Vec::new();
+    };
+
+    let mut known_keys: Vec<&str> = vec!["all"];
+    known_keys.extend(RuleCategory::ALL.iter().map(|c| c.as_key()));
+    known_keys.extend(known_rules.iter().map(String::as_str));
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut diagnostics = Vec::new();
+
+    let tables = [
+        (
+            LintTable::Workspace,
+            doc.get_ref().get("workspace").and_then(|w| de_table_at(w, &["lints", "omnia"])),
+        ),
+        (LintTable::Crate, doc.get_ref().get("lints").and_then(|l| de_table_at(l, &["omnia"]))),
+    ];
+
+    for (origin, table) in tables {
+        let Some(table) = table else { continue };
+
+        for (key, value) in table {
+            let key_str: &str = key.get_ref();
+            let (line, column) = line_col(content, key.span().start);
+            let snippet = lines.get(line).map(|l| l.to_string());
+
+            if !known_keys.contains(&key_str) {
+                let suggestion = closest_match(key_str, known_keys.iter().copied());
+                let mut message = format!("unknown lint `{}` in {}", key_str, origin.header());
+                if let Some(s) = suggestion {
+                    message.push_str(&format!("\n\nDid you mean `{}`?", s));
+                }
+                diagnostics.push(Diagnostic {
+                    line: line + 1,
+                    column,
+                    end_column: column + key.span().len(),
+                    severity: RuleSeverity::Warning,
+                    rule_id: "unknown_lint".to_string(),
+                    rule_name: "Unknown Lint".to_string(),
+                    category: RuleCategory::Lint,
+                    message,
+                    fix_template: suggestion.map(|s| format!("{} = ...", s)),
+                    source_snippet: snippet.clone(),
+                });
+            }
+
+            let problem = match raw_level(value.get_ref()) {
+                Ok(level) if LintLevel::parse(level).is_some() => None,
+                Ok(level) => Some((
+                    format!("`\"{}\"`", level),
+                    closest_match(level, LEVEL_NAMES.iter().copied()),
+                )),
+                Err(shape) => Some((shape, None)),
+            };
+
+            if let Some((bad, suggestion)) = problem {
+                let (line, column) = line_col(content, value.span().start);
+                let mut message = format!(
+                    "invalid level {} for `{}` in {}; expected one of `allow`, `warn`, `deny`, `forbid`",
+                    bad,
+                    key_str,
+                    origin.header()
+                );
+                if let Some(s) = suggestion {
+                    message.push_str(&format!("\n\nDid you mean `\"{}\"`?", s));
+                }
+                diagnostics.push(Diagnostic {
+                    line: line + 1,
+                    column,
+                    end_column: column + value.span().len(),
+                    severity: RuleSeverity::Error,
+                    rule_id: "invalid_lint_level".to_string(),
+                    rule_name: "Invalid Lint Level".to_string(),
+                    category: RuleCategory::Lint,
+                    message,
+                    fix_template: suggestion.map(|s| format!("{} = \"{}\"", key_str, s)),
+                    source_snippet: lines.get(line).map(|l| l.to_string()),
+                });
+            }
+        }
+    }
+
+    // Table iteration is keyed, not positional; report in file order
+    diagnostics.sort_by_key(|d| (d.line, d.column));
+    diagnostics
+}
+
+/// Follow a path of nested keys through a parsed TOML value.
+fn de_table_at<'a, 'i>(
+    value: &'a toml::Spanned<DeValue<'i>>, path: &[&str],
+) -> Option<&'a DeTable<'i>> {
+    let mut current = value.get_ref().as_table()?;
+    for key in path {
+        current = current.get(*key)?.get_ref().as_table()?;
+    }
+    Some(current)
+}
+
+/// Extract the level string from a lint value.
+///
+/// Mirrors [`parse_lint_level`]: a plain string or a table with a `level`
+/// key are accepted. Any other shape is described in the error.
+fn raw_level<'a>(value: &'a DeValue<'_>) -> Result<&'a str, String> {
+    match value {
+        DeValue::String(s) => Ok(s.as_ref()),
+        DeValue::Table(t) => match t.get("level").map(|v| v.get_ref()) {
+            Some(DeValue::String(s)) => Ok(s.as_ref()),
+            Some(other) => Err(format!("`{}`", other.type_str())),
+            None => Err("(table without `level`)".to_string()),
+        },
+        other => Err(format!("`{}`", other.type_str())),
+    }
+}
+
+/// Convert a byte offset into a 0-indexed `(line, column)` pair.
+fn line_col(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count();
+    let column = before.rfind('\n').map_or(before.len(), |nl| before.len() - nl - 1);
+    (line, column)
+}
+
+/// Find the candidate closest to `name` by edit distance.
+///
+/// Only candidates within a third of the name's length are suggested, so
+/// unrelated names do not produce misleading hints.
+pub(crate) fn closest_match<'a>(
+    name: &str, candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let max_distance = (name.len() / 3).max(1);
+    candidates
+        .into_iter()
+        .map(|c| (edit_distance(name, c), c))
+        .filter(|&(d, _)| d <= max_distance)
+        .min_by_key(|&(d, _)| d)
+        .map(|(_, c)| c)
+}
+
+/// Edit distance between two strings, counting adjacent transpositions as a
+/// single edit (optimal string alignment).
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
@@ -179,12 +535,19 @@ fn parse_lint_level(value: &toml::Value) -> Option<LintLevel> {
 }
 
 /// Merge a TOML table of lint entries into a [`CargoLintConfig`].
-fn merge_toml_table(config: &mut CargoLintConfig, table: &toml::value::Table) {
+fn merge_toml_table(config: &mut CargoLintConfig, table: &toml::value::Table, origin: LintTable) {
     for (key, value) in table {
         let Some(level) = parse_lint_level(value) else {
             continue;
         };
 
+        config.entries.push(LevelEntry {
+            key: key.clone(),
+            level,
+            table: origin,
+            source: config.source.clone(),
+        });
+
         if key == "all" {
             // Supercategory
             config.all = Some(level);
@@ -198,6 +561,67 @@ fn merge_toml_table(config: &mut CargoLintConfig, table: &toml::value::Table) {
     }
 }
 
+/// Parse a `metadata.omnia.providers` table: one entry per trait name,
+/// with an optional `crate` path and a list of `methods`.
+///
+/// Each method needs a `name`; `arity` defaults to 0, `receiver` to
+/// `"&self"`, `async` to `true` and `returns` to an unknown type.
+fn parse_provider_table(table: &toml::value::Table) -> Result<Vec<ProviderTrait>> {
+    table
+        .iter()
+        .map(|(name, value)| {
+            let entry =
+                value.as_table().with_context(|| format!("provider `{}` must be a table", name))?;
+            let crate_path = match entry.get("crate") {
+                Some(v) => v
+                    .as_str()
+                    .with_context(|| format!("`crate` of provider `{}` must be a string", name))?,
+                None => "",
+            };
+            let methods = match entry.get("methods") {
+                Some(v) => v
+                    .as_array()
+                    .with_context(|| format!("`methods` of provider `{}` must be an array", name))?
+                    .iter()
+                    .map(|method| parse_provider_method(name, method))
+                    .collect::<Result<Vec<_>>>()?,
+                None => Vec::new(),
+            };
+            Ok(ProviderTrait::new(name, crate_path, methods))
+        })
+        .collect()
+}
+
+/// Parse one `{ name, arity, receiver, async, returns }` method entry of a
+/// provider.
+fn parse_provider_method(provider: &str, value: &toml::Value) -> Result<ProviderMethod> {
+    let invalid =
+        |field: &str| format!("invalid `{}` in a method of provider `{}`", field, provider);
+
+    let table = value.as_table().with_context(|| invalid("methods"))?;
+    let name = table.get("name").and_then(|v| v.as_str()).with_context(|| invalid("name"))?;
+    let mut method = ProviderMethod::new(name, 0);
+
+    if let Some(arity) = table.get("arity") {
+        method.arity = arity
+            .as_integer()
+            .and_then(|n| usize::try_from(n).ok())
+            .with_context(|| invalid("arity"))?;
+    }
+    if let Some(receiver) = table.get("receiver") {
+        method.receiver =
+            receiver.as_str().and_then(Receiver::parse).with_context(|| invalid("receiver"))?;
+    }
+    if let Some(is_async) = table.get("async") {
+        method.is_async = is_async.as_bool().with_context(|| invalid("async"))?;
+    }
+    if let Some(returns) = table.get("returns") {
+        method.returns = Some(returns.as_str().with_context(|| invalid("returns"))?.to_string());
+    }
+
+    Ok(method)
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -227,7 +651,7 @@ error_generic_unwrap = "allow"
             .unwrap();
 
         let mut config = CargoLintConfig::default();
-        merge_toml_table(&mut config, table);
+        merge_toml_table(&mut config, table, LintTable::Workspace);
 
         assert_eq!(config.all, Some(LintLevel::Warn));
         assert_eq!(config.categories.get(&RuleCategory::Handler), Some(&LintLevel::Deny));
@@ -254,16 +678,34 @@ handler = { level = "deny", priority = 1 }
             .unwrap();
 
         let mut config = CargoLintConfig::default();
-        merge_toml_table(&mut config, table);
+        merge_toml_table(&mut config, table, LintTable::Workspace);
 
         assert_eq!(config.all, Some(LintLevel::Warn));
         assert_eq!(config.categories.get(&RuleCategory::Handler), Some(&LintLevel::Deny));
     }
 
     #[test]
-    fn test_effective_level_precedence() {
+    fn test_effective_level_any_alias() {
         let mut config = CargoLintConfig::default();
-        config.all = Some(LintLevel::Warn);
+        config.categories.insert(RuleCategory::Wasm, LintLevel::Deny);
+        config.rules.insert("std_fs".to_string(), LintLevel::Allow);
+
+        assert_eq!(
+            config.effective_level_any(&["wasm_std_fs", "std_fs"], RuleCategory::Wasm),
+            Some(LintLevel::Allow)
+        );
+        assert_eq!(
+            config.effective_level_any(&["wasm_std_net", "std_net"], RuleCategory::Wasm),
+            Some(LintLevel::Deny)
+        );
+    }
+
+    #[test]
+    fn test_effective_level_precedence() {
+        let mut config = CargoLintConfig {
+            all: Some(LintLevel::Warn),
+            ..Default::default()
+        };
         config.categories.insert(RuleCategory::Error, LintLevel::Deny);
         config.rules.insert("error_generic_unwrap".to_string(), LintLevel::Allow);
 
@@ -292,8 +734,10 @@ handler = { level = "deny", priority = 1 }
 
     #[test]
     fn test_merge_crate_wins() {
-        let mut workspace = CargoLintConfig::default();
-        workspace.all = Some(LintLevel::Warn);
+        let mut workspace = CargoLintConfig {
+            all: Some(LintLevel::Warn),
+            ..Default::default()
+        };
         workspace.categories.insert(RuleCategory::Handler, LintLevel::Deny);
 
         let mut crate_level = CargoLintConfig::default();
@@ -304,4 +748,195 @@ handler = { level = "deny", priority = 1 }
         assert_eq!(workspace.all, Some(LintLevel::Warn));
         assert_eq!(workspace.categories.get(&RuleCategory::Handler), Some(&LintLevel::Allow));
     }
+
+    #[test]
+    fn test_explain_orders_candidates_by_precedence() {
+        let dir = tempfile::tempdir().unwrap();
+        let cargo_toml = dir.path().join("Cargo.toml");
+        std::fs::write(
+            &cargo_toml,
+            r#"
+[workspace.lints.omnia]
+all = "warn"
+error_generic_unwrap = "deny"
+
+[lints.omnia]
+error = "warn"
+error_generic_unwrap = "allow"
+"#,
+        )
+        .unwrap();
+
+        let config = load_cargo_lint_config(&cargo_toml).unwrap();
+        let candidates = config.explain(&["error_generic_unwrap"], RuleCategory::Error);
+
+        let chain: Vec<_> =
+            candidates.iter().map(|c| (c.scope, c.entry.table, c.entry.level, c.wins)).collect();
+        assert_eq!(
+            chain,
+            vec![
+                (LevelScope::Rule, LintTable::Crate, LintLevel::Allow, true),
+                (LevelScope::Rule, LintTable::Workspace, LintLevel::Deny, false),
+                (LevelScope::Category, LintTable::Crate, LintLevel::Warn, false),
+                (LevelScope::All, LintTable::Workspace, LintLevel::Warn, false),
+            ]
+        );
+        assert_eq!(candidates[0].entry.source.as_deref(), Some(cargo_toml.as_path()));
+        assert_eq!(
+            config.effective_level("error_generic_unwrap", RuleCategory::Error),
+            Some(candidates[0].entry.level)
+        );
+    }
+
+    #[test]
+    fn test_load_provider_traits() {
+        let dir = tempfile::tempdir().unwrap();
+        let cargo_toml = dir.path().join("Cargo.toml");
+        std::fs::write(
+            &cargo_toml,
+            r#"
+[workspace.metadata.omnia.providers.BlobStore]
+crate = "shared_sdk"
+methods = [{ name = "put", arity = 2 }]
+
+[package.metadata.omnia.providers.BlobStore]
+crate = "my_sdk"
+methods = [
+    { name = "put", arity = 2 },
+    { name = "list", receiver = "&mut self", async = false, returns = "Vec<String>" },
+]
+"#,
+        )
+        .unwrap();
+
+        let config = load_cargo_lint_config(&cargo_toml).unwrap();
+        assert_eq!(config.providers.len(), 2);
+
+        let blob = &config.providers[1];
+        assert_eq!(blob.crate_path, "my_sdk");
+        assert_eq!(blob.methods[0], ProviderMethod::new("put", 2));
+        assert_eq!(blob.methods[1].arity, 0);
+        assert_eq!(blob.methods[1].receiver, Receiver::RefMut);
+        assert!(!blob.methods[1].is_async);
+        assert_eq!(blob.methods[1].returns.as_deref(), Some("Vec<String>"));
+
+        std::fs::write(
+            &cargo_toml,
+            "[package.metadata.omnia.providers.BlobStore]\nmethods = [{ arity = 1 }]\n",
+        )
+        .unwrap();
+        let err = load_cargo_lint_config(&cargo_toml).unwrap_err();
+        assert!(format!("{:#}", err).contains("invalid `name`"));
+    }
+
+    #[test]
+    fn test_load_require_reason() {
+        let dir = tempfile::tempdir().unwrap();
+        let cargo_toml = dir.path().join("Cargo.toml");
+        std::fs::write(
+            &cargo_toml,
+            "[workspace.metadata.omnia]\nrequire_reason = true\n\n[package.metadata.omnia]\nrequire_reason = false\n",
+        )
+        .unwrap();
+        let config = load_cargo_lint_config(&cargo_toml).unwrap();
+        assert_eq!(config.require_reason, Some(false));
+
+        let mut workspace = CargoLintConfig {
+            require_reason: Some(true),
+            ..Default::default()
+        };
+        workspace.merge(&CargoLintConfig::default());
+        assert_eq!(workspace.require_reason, Some(true));
+
+        std::fs::write(&cargo_toml, "[package.metadata.omnia]\nrequire_reason = \"yes\"\n")
+            .unwrap();
+        let err = load_cargo_lint_config(&cargo_toml).unwrap_err();
+        assert!(format!("{:#}", err).contains("`require_reason` must be a boolean"));
+    }
+
+    #[test]
+    fn test_explain_empty_without_entries() {
+        let config = CargoLintConfig::default();
+        assert!(config.explain(&["handler_generic_p"], RuleCategory::Handler).is_empty());
+    }
+
+    #[test]
+    fn test_explain_alias_entry_wins() {
+        let dir = tempfile::tempdir().unwrap();
+        let cargo_toml = dir.path().join("Cargo.toml");
+        std::fs::write(&cargo_toml, "[lints.omnia]\nwasm = \"deny\"\nstd_fs = \"allow\"\n")
+            .unwrap();
+
+        let config = load_cargo_lint_config(&cargo_toml).unwrap();
+        let ids = ["wasm_std_fs", "wasm_std_fs", "std_fs"];
+        let candidates = config.explain(&ids, RuleCategory::Wasm);
+
+        let chain: Vec<_> =
+            candidates.iter().map(|c| (c.scope, c.entry.key.as_str(), c.wins)).collect();
+        assert_eq!(
+            chain,
+            vec![(LevelScope::Alias, "std_fs", true), (LevelScope::Category, "wasm", false)]
+        );
+        assert_eq!(candidates[0].entry.level, LintLevel::Allow);
+        assert_eq!(
+            config.effective_level_any(&ids, RuleCategory::Wasm),
+            Some(candidates[0].entry.level)
+        );
+    }
+
+    fn known() -> Vec<String> {
+        vec!["error_generic_unwrap".to_string(), "perf_clone_in_loop".to_string()]
+    }
+
+    #[test]
+    fn test_validate_reports_unknown_key_with_suggestion() {
+        let content = r#"[package]
+name = "demo"
+
+[lints.omnia]
+error_generic_unwarp = "allow"
+"#;
+        let diagnostics = validate_lint_tables(content, &known());
+        assert_eq!(diagnostics.len(), 1);
+        let diag = &diagnostics[0];
+        assert_eq!(diag.rule_id, "unknown_lint");
+        assert_eq!((diag.line, diag.column), (5, 0));
+        assert!(diag.message.contains("Did you mean `error_generic_unwrap`?"));
+    }
+
+    #[test]
+    fn test_validate_reports_invalid_levels() {
+        let content = r#"[workspace.lints.omnia]
+handler = "deyn"
+wasm = { level = "deny", priority = 1 }
+security = 3
+error = { priority = 1 }
+"#;
+        let diagnostics = validate_lint_tables(content, &known());
+        let invalid: Vec<_> =
+            diagnostics.iter().filter(|d| d.rule_id == "invalid_lint_level").collect();
+        assert_eq!(invalid.len(), 3, "{:?}", diagnostics);
+        assert_eq!((invalid[0].line, invalid[0].column), (2, 10));
+        assert!(invalid[0].message.contains("Did you mean `\"deny\"`?"));
+        assert_eq!(invalid[1].line, 4);
+        assert_eq!(invalid[2].line, 5);
+    }
+
+    #[test]
+    fn test_validate_accepts_known_keys() {
+        let content = r#"[workspace.lints.omnia]
+all = "warn"
+strong_typing = "deny"
+perf_clone_in_loop = { level = "allow", priority = 2 }
+"#;
+        assert!(validate_lint_tables(content, &known()).is_empty());
+    }
+
+    #[test]
+    fn test_closest_match() {
+        let candidates = ["error_generic_unwrap", "error_panic_macro", "handler"];
+        assert_eq!(closest_match("error_generic_unwarp", candidates), Some("error_generic_unwrap"));
+        assert_eq!(closest_match("handlr", candidates), Some("handler"));
+        assert_eq!(closest_match("completely_different", candidates), None);
+    }
 }
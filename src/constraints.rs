@@ -5,6 +5,7 @@
 
 use std::collections::HashSet;
 
+use crate::providers::ProviderCatalog;
 use crate::rules::RuleSeverity;
 
 /// A pattern that is forbidden in Omnia WASM32 code.
@@ -42,10 +43,10 @@ pub fn forbidden_crates() -> HashSet<&'static str> {
         // Redis clients - use StateStore trait
         "redis",
         "fred",
-        // Kafka clients - use Publisher trait
+        // Kafka clients - use Publish trait
         "rdkafka",
         "kafka",
-        // RabbitMQ - use Publisher trait
+        // RabbitMQ - use Publish trait
         "lapin",
         "amqp",
         // Async runtimes - WASI provides executor
@@ -166,14 +167,6 @@ pub fn forbidden_patterns() -> Vec<ForbiddenPattern> {
             alternative: "Async delays are not directly available in WASI. Consider restructuring logic.",
             severity: RuleSeverity::Error,
         },
-        ForbiddenPattern {
-            id: "println_debug",
-            name: "Println/Eprintln",
-            reason: "println!/eprintln! may not be visible in WASM32. Use tracing.",
-            patterns: vec![r"println!", r"eprintln!", r"dbg!"],
-            alternative: "Use tracing macros: info!, warn!, error!, debug!",
-            severity: RuleSeverity::Hint,
-        },
     ]
 }
 
@@ -187,7 +180,7 @@ pub struct OmniaContext {
     pub forbidden_patterns: Vec<ForbiddenPattern>,
 
     /// Known provider traits.
-    pub provider_traits: Vec<&'static str>,
+    pub providers: ProviderCatalog,
 }
 
 impl OmniaContext {
@@ -196,14 +189,7 @@ impl OmniaContext {
         Self {
             forbidden_crates: forbidden_crates(),
             forbidden_patterns: forbidden_patterns(),
-            provider_traits: vec![
-                "Config",
-                "HttpRequest",
-                "Publisher",
-                "StateStore",
-                "Identity",
-                "TableStore",
-            ],
+            providers: ProviderCatalog::new(),
         }
     }
 
@@ -214,7 +200,7 @@ impl OmniaContext {
 
     /// Check if a name is a known provider trait.
     pub fn is_provider_trait(&self, name: &str) -> bool {
-        self.provider_traits.contains(&name)
+        self.providers.is_provider_trait(name)
     }
 }
 
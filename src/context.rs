@@ -0,0 +1,235 @@
+//! Per-file analysis context shared by every pass.
+//!
+//! [`DiagnosticsEngine::analyze`](crate::DiagnosticsEngine::analyze) builds
+//! one [`FileContext`] per file: the source is split into lines, classified
+//! into code, string and comment regions, and parsed with `syn` once. Regex
+//! passes, suppression scoping and the semantic analyzer all read from it,
+//! so adding an AST-based check never costs another parse.
+//!
+//! A file that does not parse as a whole is split into top-level chunks;
+//! chunks that fail on their own are blanked out and the rest is parsed
+//! again, so one half-typed item does not hide every other item from the
+//! AST passes. Blanking keeps byte offsets, so spans still match `source`.
+
+use std::ops::Range;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::tokens::TokenMap;
+
+/// Start of a line that begins a new top-level item.
+static ITEM_START: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?:#|pub\b|fn\b|async\b|const\b|unsafe\b|extern\b|impl\b|struct\b|enum\b|union\b|trait\b|type\b|mod\b|use\b|static\b|macro_rules!)",
+    )
+    .expect("valid regex: item_start")
+});
+
+/// Everything known about one source file before any rule runs.
+pub struct FileContext<'a> {
+    /// Full source text.
+    pub source: &'a str,
+
+    /// Source lines, without line terminators.
+    pub lines: Vec<&'a str>,
+
+    /// Byte offset at which each line starts.
+    pub line_starts: Vec<usize>,
+
+    /// Code, string and comment regions.
+    pub tokens: TokenMap,
+
+    /// Parsed syntax tree. When the file does not parse, this holds the
+    /// top-level items that do, or `None` if nothing could be recovered.
+    pub ast: Option<syn::File>,
+
+    /// Error from parsing the whole file, if it does not parse.
+    pub parse_error: Option<syn::Error>,
+}
+
+impl<'a> FileContext<'a> {
+    /// Split, classify and parse `source`.
+    pub fn new(source: &'a str) -> Self {
+        let line_starts =
+            std::iter::once(0).chain(source.match_indices('\n').map(|(i, _)| i + 1)).collect();
+
+        let tokens = TokenMap::new(source);
+        let (ast, parse_error) = match syn::parse_file(source) {
+            Ok(file) => (Some(file), None),
+            Err(err) => (recover_items(source, &tokens), Some(err)),
+        };
+
+        Self {
+            source,
+            lines: source.lines().collect(),
+            line_starts,
+            tokens,
+            ast,
+            parse_error,
+        }
+    }
+
+    /// Line at a 0-indexed position, or `""` past the end.
+    pub fn line(&self, line_idx: usize) -> &'a str {
+        self.lines.get(line_idx).copied().unwrap_or("")
+    }
+
+    /// Byte offset of a 0-indexed line and byte column.
+    pub fn offset(&self, line_idx: usize, column: usize) -> usize {
+        self.line_starts.get(line_idx).map_or(usize::MAX, |start| start + column)
+    }
+
+    /// 0-indexed line and byte column of a byte offset.
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let line_idx = self.line_starts.partition_point(|&start| start <= offset).saturating_sub(1);
+        (line_idx, offset - self.line_starts.get(line_idx).copied().unwrap_or(0))
+    }
+
+    /// Check if a 0-indexed line and byte column lie in code.
+    pub fn is_code(&self, line_idx: usize, column: usize) -> bool {
+        self.tokens.is_code(self.offset(line_idx, column))
+    }
+}
+
+/// Parse the top-level items of a file that does not parse as a whole.
+///
+/// Chunks that fail to parse on their own are replaced with whitespace and
+/// the remaining text is parsed again.
+fn recover_items(source: &str, tokens: &TokenMap) -> Option<syn::File> {
+    let mut blanked = source.as_bytes().to_vec();
+    let mut recovered = false;
+
+    for chunk in top_level_chunks(source, tokens) {
+        if syn::parse_file(&source[chunk.clone()]).is_ok() {
+            recovered = true;
+            continue;
+        }
+        for byte in &mut blanked[chunk] {
+            if *byte != b'\n' {
+                *byte = b' ';
+            }
+        }
+    }
+
+    // Whole UTF-8 sequences were blanked, so the result is still valid text
+    let blanked = String::from_utf8(blanked).ok()?;
+    recovered.then(|| syn::parse_file(&blanked).ok()).flatten()
+}
+
+/// Split a file into the byte ranges of its top-level items.
+///
+/// An item ends at a `;` or closing `}` outside any delimiter. Delimiters in
+/// strings and comments are ignored. When delimiters are unbalanced, a line
+/// that starts with an item keyword in the first column begins a new chunk.
+fn top_level_chunks(source: &str, tokens: &TokenMap) -> Vec<Range<usize>> {
+    let bytes = source.as_bytes();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut depth = 0usize;
+    // Last code byte of the current chunk, to keep attributes with their item
+    let mut last_code = None;
+
+    for (offset, &byte) in bytes.iter().enumerate() {
+        let line_start = offset == 0 || bytes[offset - 1] == b'\n';
+        if line_start
+            && last_code.is_some_and(|b| depth > 0 || b != b']')
+            && ITEM_START.is_match(&source[offset..])
+        {
+            chunks.push(start..offset);
+            start = offset;
+            depth = 0;
+            last_code = None;
+        }
+
+        if byte.is_ascii_whitespace() || !tokens.is_code(offset) {
+            continue;
+        }
+        last_code = Some(byte);
+        match byte {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' => depth = depth.saturating_sub(1),
+            b'}' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    chunks.push(start..offset + 1);
+                    start = offset + 1;
+                    last_code = None;
+                }
+            }
+            b';' if depth == 0 => {
+                chunks.push(start..offset + 1);
+                start = offset + 1;
+                last_code = None;
+            }
+            _ => {}
+        }
+    }
+    if !source[start..].trim().is_empty() {
+        chunks.push(start..bytes.len());
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_table() {
+        let ctx = FileContext::new("line0\nline1\nline2");
+        assert_eq!(ctx.offset(0, 0), 0);
+        assert_eq!(ctx.offset(1, 0), 6);
+        assert_eq!(ctx.offset(2, 3), 15);
+        assert_eq!(ctx.line_col(15), (2, 3));
+        assert_eq!(ctx.line_col(5), (0, 5));
+        assert_eq!(ctx.line_col(6), (1, 0));
+        assert_eq!(ctx.line(1), "line1");
+        assert_eq!(ctx.line(9), "");
+    }
+
+    #[test]
+    fn test_parses_once() {
+        let ctx = FileContext::new("fn main() { let s = \"x.unwrap()\"; }");
+        assert!(ctx.ast.is_some());
+        assert!(!ctx.is_code(0, 21));
+        assert!(ctx.is_code(0, 0));
+
+        let broken = FileContext::new("fn main( {");
+        assert!(broken.ast.is_none());
+        assert!(broken.parse_error.is_some());
+    }
+
+    #[test]
+    fn test_recovers_items_that_parse() {
+        let source = "use std::fmt;\n\nfn broken() {\n    let x = ;\n}\n\n#[derive(Debug)]\nstruct Kept;\n\nfn half(\n\nfn also_kept() -> u8 { 1 }\n";
+        let ctx = FileContext::new(source);
+        assert!(ctx.parse_error.is_some());
+
+        let file = ctx.ast.as_ref().expect("partial AST");
+        let names: Vec<String> = file
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::Item::Fn(f) => Some(f.sig.ident.to_string()),
+                syn::Item::Struct(s) => Some(format!("{} {}", s.ident, s.attrs.len())),
+                syn::Item::Use(_) => Some("use".to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names, vec!["use", "Kept 1", "also_kept"]);
+
+        // Spans still point into the original source
+        let syn::Item::Fn(kept) = &file.items[2] else { panic!("expected fn") };
+        assert_eq!(ctx.line_col(kept.sig.ident.span().byte_range().start), (11, 3));
+    }
+
+    #[test]
+    fn test_top_level_chunks_ignore_strings() {
+        let source = "const A: &str = \"};\";\nconst B: [u8; 2] = [0; 2];\n";
+        let chunks = top_level_chunks(source, &TokenMap::new(source));
+        let texts: Vec<&str> = chunks.iter().map(|c| source[c.clone()].trim()).collect();
+        assert_eq!(texts, vec!["const A: &str = \"};\";", "const B: [u8; 2] = [0; 2];"]);
+    }
+}
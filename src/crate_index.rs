@@ -0,0 +1,357 @@
+//! Crate-wide index for cross-file semantic analysis.
+//!
+//! Handlers often delegate to helpers in other modules of the crate
+//! (`src/providers/http.rs`, `src/repo.rs`). A [`CrateIndex`] follows the
+//! `mod` declarations from a crate root (`lib.rs` or `main.rs`), parses every
+//! module file once and records where each file sits in the module tree,
+//! the names declared and imported in every module, and the provider helpers
+//! and type definitions keyed by their crate-qualified path. The semantic
+//! analyzer resolves calls and types through it, so transitive trait usage
+//! and the serde derives of handler types are traced across files.
+//!
+//! The index holds no syntax trees, so it can be shared between the threads
+//! that lint a crate's files.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use syn::Item;
+
+use crate::config::find_cargo_toml;
+use crate::context::FileContext;
+use crate::providers::ProviderCatalog;
+use crate::semantic::{CallIndex, FunctionInfo, TypeTable, extract_functions};
+
+/// Files, names and provider helpers of one crate.
+#[derive(Debug, Default)]
+pub struct CrateIndex {
+    /// Crate root the module tree was followed from.
+    root: PathBuf,
+
+    /// Module path of each file in the tree (`""` for the root).
+    modules: HashMap<PathBuf, String>,
+
+    /// Names declared and imported across the crate.
+    calls: CallIndex<'static>,
+
+    /// Provider helpers of every file, by crate-qualified path.
+    functions: HashMap<String, FunctionInfo>,
+
+    /// Structs, enums and type aliases of every file, by crate-qualified
+    /// path.
+    types: TypeTable,
+}
+
+impl CrateIndex {
+    /// Index the crate whose root file is `root`, recognizing the provider
+    /// traits of `catalog`.
+    ///
+    /// Module files that cannot be read are skipped; files that do not
+    /// parse contribute the items that can be recovered.
+    pub fn load(root: &Path, catalog: &ProviderCatalog) -> Self {
+        let mut modules = HashMap::new();
+        let mut sources = Vec::new();
+        let mut queue = vec![(root.to_path_buf(), String::new())];
+
+        while let Some((path, module)) = queue.pop() {
+            let key = canonical(&path);
+            if modules.contains_key(&key) {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(&path) else { continue };
+            let Some(file) = FileContext::new(&content).ast else { continue };
+
+            let dir = child_dir(&path, module.is_empty());
+            collect_mod_files(&file.items, &module, &dir, &mut queue);
+            modules.insert(key, module.clone());
+            sources.push((file, module));
+        }
+
+        let files: Vec<(&syn::File, &str)> =
+            sources.iter().map(|(file, module)| (file, module.as_str())).collect();
+        let mut calls = CallIndex::new(catalog);
+        calls.add_files(&files);
+
+        let mut types = TypeTable::default();
+        types.add_files(&calls, &files);
+
+        let mut functions = HashMap::new();
+        for (file, module) in files {
+            functions.extend(extract_functions(file, module, &calls));
+        }
+
+        Self {
+            root: root.to_path_buf(),
+            modules,
+            calls,
+            functions,
+            types,
+        }
+    }
+
+    /// Index the crate a file belongs to, if the file is in its module tree.
+    ///
+    /// The crate is found from the nearest `Cargo.toml`; its `src/lib.rs`
+    /// and `src/main.rs` are tried as roots in that order.
+    pub fn discover(path: &Path, catalog: &ProviderCatalog) -> Option<Self> {
+        crate_roots(path)
+            .into_iter()
+            .map(|root| Self::load(&root, catalog))
+            .find(|krate| krate.contains(path))
+    }
+
+    /// Crate root the module tree was followed from.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Check if a file is in the crate's module tree.
+    pub fn contains(&self, path: &Path) -> bool {
+        self.modules.contains_key(&canonical(path))
+    }
+
+    /// Module path of a file in the crate, e.g. `providers::http`.
+    pub fn module_of(&self, path: &Path) -> Option<&str> {
+        self.modules.get(&canonical(path)).map(String::as_str)
+    }
+
+    /// Provider helpers of every file, by crate-qualified path.
+    pub fn functions(&self) -> &HashMap<String, FunctionInfo> {
+        &self.functions
+    }
+
+    pub(crate) fn calls(&self) -> &CallIndex<'static> {
+        &self.calls
+    }
+
+    pub(crate) fn types(&self) -> &TypeTable {
+        &self.types
+    }
+}
+
+/// Crate roots that may contain `path`: `src/lib.rs` and `src/main.rs` next
+/// to the nearest `Cargo.toml`, where they exist.
+pub fn crate_roots(path: &Path) -> Vec<PathBuf> {
+    let Some(manifest) = find_cargo_toml(path) else {
+        return Vec::new();
+    };
+    let src = manifest.with_file_name("src");
+    ["lib.rs", "main.rs"].iter().map(|name| src.join(name)).filter(|root| root.is_file()).collect()
+}
+
+/// Directory that holds the files of a file's child modules.
+///
+/// Children of the crate root and of `mod.rs` files live next to them;
+/// children of `foo.rs` live in `foo/`.
+fn child_dir(path: &Path, is_root: bool) -> PathBuf {
+    let parent = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+    if is_root || path.file_name().is_some_and(|name| name == "mod.rs") {
+        return parent;
+    }
+    match path.file_stem() {
+        Some(stem) => parent.join(stem),
+        None => parent,
+    }
+}
+
+/// Queue the file of every `mod name;` declaration, including those inside
+/// inline modules, with its module path.
+fn collect_mod_files(items: &[Item], module: &str, dir: &Path, queue: &mut Vec<(PathBuf, String)>) {
+    for item in items {
+        let Item::Mod(item_mod) = item else { continue };
+        let name = item_mod.ident.to_string();
+        let child = if module.is_empty() { name.clone() } else { format!("{}::{}", module, name) };
+
+        match &item_mod.content {
+            Some((_, content)) => collect_mod_files(content, &child, &dir.join(&name), queue),
+            None => {
+                if let Some(path) = mod_file(item_mod, dir) {
+                    queue.push((path, child));
+                }
+            }
+        }
+    }
+}
+
+/// File of an out-of-line module: its `#[path = ".."]`, `name.rs` or
+/// `name/mod.rs`.
+fn mod_file(item_mod: &syn::ItemMod, dir: &Path) -> Option<PathBuf> {
+    let explicit = item_mod.attrs.iter().find_map(|attr| {
+        let syn::Meta::NameValue(nv) = &attr.meta else { return None };
+        if !nv.path.is_ident("path") {
+            return None;
+        }
+        match &nv.value {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(lit),
+                ..
+            }) => Some(dir.join(lit.value())),
+            _ => None,
+        }
+    });
+    if explicit.is_some() {
+        return explicit;
+    }
+
+    let name = item_mod.ident.to_string();
+    [dir.join(format!("{}.rs", name)), dir.join(&name).join("mod.rs")]
+        .into_iter()
+        .find(|path| path.is_file())
+}
+
+/// Canonical form of a path for lookups, or the path itself if it does not
+/// exist.
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::Diagnostic;
+    use crate::rules::RuleCategory;
+
+    fn write(dir: &Path, path: &str, content: &str) {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_follows_mod_declarations() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "Cargo.toml", "[package]\nname = \"demo\"\n");
+        write(dir.path(), "src/lib.rs", "mod handlers;\nmod providers;\n");
+        write(dir.path(), "src/handlers.rs", "mod list;\n");
+        write(dir.path(), "src/handlers/list.rs", "");
+        write(
+            dir.path(),
+            "src/providers/mod.rs",
+            "pub mod inline { mod deep; }\n#[path = \"extra.rs\"]\nmod other;\n",
+        );
+        write(dir.path(), "src/providers/inline/deep.rs", "");
+        write(dir.path(), "src/providers/extra.rs", "");
+        write(dir.path(), "src/orphan.rs", "");
+
+        let krate =
+            CrateIndex::discover(&dir.path().join("src/handlers/list.rs"), &ProviderCatalog::new())
+                .expect("in crate");
+        let src = dir.path().join("src");
+        assert_eq!(krate.module_of(&src.join("lib.rs")), Some(""));
+        assert_eq!(krate.module_of(&src.join("handlers/list.rs")), Some("handlers::list"));
+        assert_eq!(krate.module_of(&src.join("providers/mod.rs")), Some("providers"));
+        assert_eq!(
+            krate.module_of(&src.join("providers/inline/deep.rs")),
+            Some("providers::inline::deep")
+        );
+        assert_eq!(krate.module_of(&src.join("providers/extra.rs")), Some("providers::other"));
+        assert!(!krate.contains(&src.join("orphan.rs")));
+        assert!(CrateIndex::discover(&src.join("orphan.rs"), &ProviderCatalog::new()).is_none());
+    }
+
+    #[test]
+    fn test_indexes_helpers_by_crate_path() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "Cargo.toml", "[package]\nname = \"demo\"\n");
+        write(dir.path(), "src/main.rs", "mod repo;\n");
+        write(
+            dir.path(),
+            "src/repo.rs",
+            "pub async fn load<P: TableStore>(provider: &P) -> Result<()> {\n    provider.query(\"q\").await\n}\n",
+        );
+
+        let krate = CrateIndex::discover(&dir.path().join("src/repo.rs"), &ProviderCatalog::new())
+            .expect("in crate");
+        assert!(krate.root().ends_with("main.rs"));
+        let load = &krate.functions()["repo::load"];
+        assert!(load.direct_trait_usage.contains("TableStore"));
+    }
+
+    #[test]
+    fn test_handler_bounds_follow_helpers_in_other_files() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "Cargo.toml", "[package]\nname = \"demo\"\n");
+        write(dir.path(), "src/lib.rs", "mod handlers;\nmod repo;\n");
+        write(
+            dir.path(),
+            "src/repo.rs",
+            "pub async fn load<P: TableStore>(provider: &P) -> Result<()> {\n    provider.query(\"q\").await\n}\n\npub async fn notify<P: Publish>(provider: &P) -> Result<()> {\n    provider.send(\"t\", b\"m\").await\n}\n",
+        );
+        let handler = "use crate::repo;\n\nimpl<P: TableStore> Handler<P> for ListRequest {\n    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {\n        repo::load(&ctx.provider).await?;\n        crate::repo::notify(&ctx.provider).await?;\n        Ok(Reply::ok(()))\n    }\n}\n";
+        let path = dir.path().join("src/handlers.rs");
+        write(dir.path(), "src/handlers.rs", handler);
+
+        let krate = CrateIndex::discover(&path, &ProviderCatalog::new()).expect("in crate");
+        let ctx = FileContext::new(handler);
+        let result = crate::semantic::SemanticAnalyzer::new().analyze_in_crate(&ctx, &krate, &path);
+        let found: Vec<&Diagnostic> =
+            result.diagnostics.iter().filter(|d| d.category == RuleCategory::Provider).collect();
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].rule_id.as_str(), found[0].line), ("missing_provider_bound", 3));
+        assert!(found[0].message.contains("'Publish'"));
+
+        // On its own, the handler appears to use nothing
+        let alone = crate::semantic::SemanticAnalyzer::new().analyze_context(&ctx);
+        assert!(alone.diagnostics.iter().any(|d| d.rule_id == "unused_provider_bound"));
+    }
+
+    #[test]
+    fn test_trait_aliases_resolve_across_files() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "Cargo.toml", "[package]\nname = \"demo\"\n");
+        write(dir.path(), "src/lib.rs", "mod handlers;\npub mod provider;\n");
+        write(dir.path(), "src/provider.rs", "pub trait Provider = Config + TableStore;\n");
+        let handler = "use crate::provider::Provider;\n\nimpl<P: Provider> Handler<P> for ListRequest {\n    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {\n        ctx.provider.query(\"q\").await?;\n        Ok(Reply::ok(()))\n    }\n}\n";
+        let path = dir.path().join("src/handlers.rs");
+        write(dir.path(), "src/handlers.rs", handler);
+
+        let krate = CrateIndex::discover(&path, &ProviderCatalog::new()).expect("in crate");
+        let ctx = FileContext::new(handler);
+        let result = crate::semantic::SemanticAnalyzer::new().analyze_in_crate(&ctx, &krate, &path);
+        let handler = &result.handlers[0];
+        assert!(handler.declared_bounds.contains("TableStore"));
+        assert!(handler.declared_bounds.contains("Config"));
+        assert!(
+            !result
+                .diagnostics
+                .iter()
+                .any(|d| d.category == RuleCategory::Provider
+                    || d.rule_id == "handler_missing_bounds"),
+            "{:?}",
+            result.diagnostics
+        );
+    }
+
+    #[test]
+    fn test_serde_derives_resolve_across_files() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "Cargo.toml", "[package]\nname = \"demo\"\n");
+        write(dir.path(), "src/lib.rs", "mod dto;\nmod handlers;\nmod wire;\n");
+        write(
+            dir.path(),
+            "src/wire.rs",
+            "use crate::dto::Item;\n\nimpl Serialize for Item {\n    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {\n        todo!()\n    }\n}\n",
+        );
+        write(
+            dir.path(),
+            "src/dto.rs",
+            "#[derive(Deserialize)]\npub struct ListRequest;\n\n#[derive(Serialize)]\n#[serde(into = \"Wire\")]\npub struct Listing;\n\npub struct Wire;\n\npub type Page = Vec<Listing>;\n\npub struct Item;\n",
+        );
+        let handler = "use crate::dto::{ListRequest, Page};\n\nimpl<P: Config> Handler<P> for ListRequest {\n    type Output = Page;\n}\n\nimpl<P: Config> Handler<P> for ItemRequest {\n    type Output = crate::dto::Item;\n}\n\n#[derive(Deserialize)]\nstruct ItemRequest;\n";
+        let path = dir.path().join("src/handlers.rs");
+        write(dir.path(), "src/handlers.rs", handler);
+
+        let krate = CrateIndex::discover(&path, &ProviderCatalog::new()).expect("in crate");
+        let ctx = FileContext::new(handler);
+        let result = crate::semantic::SemanticAnalyzer::new().analyze_in_crate(&ctx, &krate, &path);
+        let found: Vec<(&str, usize, usize)> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.rule_id.starts_with("handler_serde"))
+            .map(|d| (d.rule_id.as_str(), d.line, d.column))
+            .collect();
+        assert_eq!(found, vec![("handler_serde_serialize", 4, 18)]);
+        let serialize = &result.diagnostics.iter().find(|d| d.line == 4).unwrap().message;
+        assert!(serialize.contains("`dto::Wire`"), "{serialize}");
+    }
+}
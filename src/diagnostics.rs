@@ -1,100 +1,40 @@
 //! Diagnostics engine for analyzing Omnia code.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use std::path::Path;
 
-use regex::Regex;
+use regex::{Regex, RegexSet};
+use syn::spanned::Spanned;
+use syn::visit::Visit;
 
+use crate::config::closest_match;
 use crate::constraints::{ForbiddenPattern, OmniaContext};
-use crate::rules::{RuleCategory, RuleSet, RuleSeverity};
+use crate::context::FileContext;
+use crate::crate_index::CrateIndex;
+use crate::providers::ProviderCatalog;
+use crate::registry::RuleRegistry;
+use crate::rules::{Rule, RuleCategory, RuleScope, RuleSet, RuleSeverity};
 use crate::semantic::SemanticAnalyzer;
-
-/// Parsed ignore directive from source code.
-#[derive(Debug, Clone)]
-pub struct IgnoreDirective {
-    /// Line number where the directive appears (1-indexed).
-    pub line: usize,
-    /// Whether this is a file-level directive (#![...]).
-    pub is_file_level: bool,
-    /// Rule IDs to ignore, or None for all rules.
-    pub rules: Option<HashSet<String>>,
-}
-
-impl IgnoreDirective {
-    /// Check if this directive allows (ignores) a specific rule.
-    pub fn allows(&self, rule_id: &str) -> bool {
-        match &self.rules {
-            None => true, // "all" - ignore everything
-            Some(rules) => rules.contains(rule_id) || rules.contains(&rule_id.to_lowercase()),
-        }
-    }
-}
-
-/// Parse ignore directives from source code.
-///
-/// Supports:
-/// - `#[omnia::allow(all)]` - ignore all rules for the next item
-/// - `#[omnia::allow(rule_id)]` - ignore specific rule for the next item
-/// - `#[omnia::allow(rule1, rule2)]` - ignore multiple rules for the next item
-/// - `#![omnia::allow(...)]` - file-level ignore (inner attribute)
-pub fn parse_ignore_directives(content: &str) -> Vec<IgnoreDirective> {
-    let mut directives = Vec::new();
-
-    // Pattern for #[omnia::allow(...)] or #![omnia::allow(...)]
-    let attr_pattern = Regex::new(r#"#(!?)\[omnia::allow\(([^)]+)\)\]"#).unwrap();
-
-    for (line_idx, line) in content.lines().enumerate() {
-        let trimmed = line.trim();
-
-        if let Some(caps) = attr_pattern.captures(trimmed) {
-            let is_file_level = caps.get(1).is_some_and(|m| m.as_str() == "!");
-            let rules_str = caps.get(2).map_or("", |m| m.as_str());
-
-            let rules = if rules_str.trim().to_lowercase() == "all" {
-                None
-            } else {
-                let rule_set: HashSet<String> = rules_str
-                    .split(',')
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect();
-                Some(rule_set)
-            };
-
-            directives.push(IgnoreDirective {
-                line: line_idx + 1,
-                is_file_level,
-                rules,
-            });
-        }
-    }
-
-    directives
-}
-
-/// Check if a diagnostic should be ignored based on directives.
-fn should_ignore_diagnostic(
-    diagnostic_line: usize, rule_id: &str, directives: &[IgnoreDirective],
-) -> bool {
-    for directive in directives {
-        // File-level directives apply to everything
-        if directive.is_file_level && directive.allows(rule_id) {
-            return true;
-        }
-
-        // Line-level directives apply to the next non-attribute line
-        // We check if the directive is on the line immediately before the diagnostic
-        // or within a few lines before (to handle multiple stacked attributes)
-        if !directive.is_file_level
-            && directive.line < diagnostic_line
-            && diagnostic_line <= directive.line + 10  // Allow up to 10 lines of attributes
-            && directive.allows(rule_id)
-        {
-            return true;
-        }
-    }
-    false
-}
+use crate::suppression::{
+    self, DirectiveForm, parse_directives, resolve_directive_scopes, should_ignore_diagnostic,
+};
+use crate::tokens::RegionKind;
+pub use crate::suppression::{IgnoreDirective, parse_ignore_directives};
+
+/// Rule IDs for diagnostics about the linter's own configuration.
+pub const LINT_RULES: &[&str] = &[
+    "unknown_lint",
+    "invalid_lint_level",
+    "unused_allow_directive",
+    "allow_without_reason",
+    "expired_allow_directive",
+    "invalid_allow_directive",
+    "unfulfilled_lint_expectation",
+];
+
+/// Directive names that are always accepted besides rule IDs and categories.
+const DIRECTIVE_KEYWORDS: &[&str] = &["all"];
 
 /// A diagnostic message produced by the linter.
 #[derive(Debug, Clone)]
@@ -148,8 +88,11 @@ pub struct DiagnosticsEngine {
     /// Comprehensive rule set for Omnia validation.
     rule_set: RuleSet,
 
-    /// Compiled regex patterns for forbidden patterns.
-    compiled_patterns: Vec<(ForbiddenPattern, Vec<Regex>)>,
+    /// Line-scoped rule patterns, keyed by index into `rule_set.rules`.
+    rule_patterns: PatternFamily,
+
+    /// Forbidden pattern regexes, keyed by index into `context.forbidden_patterns`.
+    forbidden_patterns: PatternFamily,
 
     /// Compiled regex for forbidden crate detection in use statements.
     crate_use_pattern: Regex,
@@ -159,95 +102,15 @@ pub struct DiagnosticsEngine {
 
     /// Semantic analyzer for deeper code analysis.
     semantic_analyzer: SemanticAnalyzer,
-}
 
-/// Check if a position (byte offset from start of content) falls inside a string literal.
-fn is_inside_string_literal_at_offset(content: &str, byte_offset: usize) -> bool {
-    let bytes = content.as_bytes();
-    let mut i = 0;
+    /// Every reportable rule id, with alias groups.
+    registry: RuleRegistry,
 
-    while i < byte_offset && i < bytes.len() {
-        // Skip comments
-        if i + 1 < bytes.len() && bytes[i] == b'/' && bytes[i + 1] == b'/' {
-            while i < bytes.len() && bytes[i] != b'\n' {
-                i += 1;
-            }
-            continue;
-        }
-
-        // Check for raw string: r"..." or r#"..."# etc.
-        if bytes[i] == b'r' && i + 1 < bytes.len() {
-            let mut hash_count = 0;
-            let mut j = i + 1;
+    /// Whether every `omnia::allow` directive must carry a `reason`.
+    require_reason: bool,
 
-            while j < bytes.len() && bytes[j] == b'#' {
-                hash_count += 1;
-                j += 1;
-            }
-
-            if j < bytes.len() && bytes[j] == b'"' {
-                j += 1;
-
-                while j < bytes.len() {
-                    if bytes[j] == b'"' {
-                        let mut closing_hashes = 0;
-                        let mut k = j + 1;
-                        while k < bytes.len() && bytes[k] == b'#' && closing_hashes < hash_count {
-                            closing_hashes += 1;
-                            k += 1;
-                        }
-                        if closing_hashes == hash_count {
-                            if byte_offset > i && byte_offset < k {
-                                return true;
-                            }
-                            i = k;
-                            break;
-                        }
-                    }
-                    j += 1;
-                }
-                continue;
-            }
-        }
-
-        // Check for regular string
-        if bytes[i] == b'"' {
-            let start = i;
-            i += 1;
-
-            while i < bytes.len() {
-                if bytes[i] == b'\\' && i + 1 < bytes.len() {
-                    i += 2;
-                    continue;
-                }
-                if bytes[i] == b'"' {
-                    if byte_offset > start && byte_offset <= i {
-                        return true;
-                    }
-                    i += 1;
-                    break;
-                }
-                i += 1;
-            }
-            continue;
-        }
-
-        i += 1;
-    }
-
-    false
-}
-
-/// Calculate byte offset for a line and column position.
-fn calculate_byte_offset(content: &str, line_idx: usize, col: usize) -> usize {
-    let mut offset = 0;
-    for (idx, line) in content.lines().enumerate() {
-        if idx == line_idx {
-            return offset + col.min(line.len());
-        }
-        offset += line.len() + 1;
-    }
-    offset
+    /// Current date (`YYYY-MM-DD`) used to expire `until` directives.
+    today: String,
 }
 
 impl DiagnosticsEngine {
@@ -255,29 +118,97 @@ impl DiagnosticsEngine {
     pub fn new() -> Self {
         let context = OmniaContext::new();
 
-        let compiled_patterns: Vec<(ForbiddenPattern, Vec<Regex>)> = context
-            .forbidden_patterns
-            .iter()
-            .map(|fp| {
-                let regexes = fp.patterns.iter().filter_map(|p| Regex::new(p).ok()).collect();
-                (fp.clone(), regexes)
-            })
-            .collect();
+        let rule_set = RuleSet::new();
+
+        let rule_patterns = PatternFamily::new(
+            rule_set
+                .rules
+                .iter()
+                .enumerate()
+                .filter(|(_, rule)| rule.is_anti_pattern && rule.scope == RuleScope::Line)
+                .map(|(idx, rule)| (idx, rule.pattern.clone()))
+                .collect(),
+        );
+
+        let forbidden_patterns = PatternFamily::new(
+            context
+                .forbidden_patterns
+                .iter()
+                .enumerate()
+                .flat_map(|(idx, fp)| {
+                    fp.patterns.iter().filter_map(move |p| Regex::new(p).ok().map(|r| (idx, r)))
+                })
+                .collect(),
+        );
 
         let semantic_analyzer = SemanticAnalyzer::new();
+        let registry = RuleRegistry::new(&rule_set, &context);
 
         Self {
             context,
-            rule_set: RuleSet::new(),
-            compiled_patterns,
+            rule_set,
+            rule_patterns,
+            forbidden_patterns,
             crate_use_pattern: Regex::new(r"use\s+(\w+)(?:::|;)").unwrap(),
             crate_extern_pattern: Regex::new(r"extern\s+crate\s+(\w+)").unwrap(),
             semantic_analyzer,
+            registry,
+            require_reason: false,
+            today: suppression::today(),
         }
     }
 
+    /// Require every `omnia::allow` directive to give a `reason`.
+    pub fn with_require_reason(mut self, require_reason: bool) -> Self {
+        self.require_reason = require_reason;
+        self
+    }
+
+    /// Recognize the provider traits of `catalog` in the bound analysis.
+    pub fn with_provider_catalog(mut self, catalog: ProviderCatalog) -> Self {
+        self.context.providers = catalog.clone();
+        self.semantic_analyzer = SemanticAnalyzer::with_catalog(catalog);
+        self
+    }
+
+    /// Provider traits the bound analysis knows.
+    pub fn provider_catalog(&self) -> &ProviderCatalog {
+        self.semantic_analyzer.catalog()
+    }
+
+    /// The registry of every rule id this engine can report.
+    pub fn registry(&self) -> &RuleRegistry {
+        &self.registry
+    }
+
+    /// Look up the category of any rule this engine can report.
+    ///
+    /// Covers the regex rule set, forbidden patterns, forbidden crates
+    /// (`forbidden_crate_<name>`), the semantic analyzer's rules and the
+    /// linter's own directive rules.
+    pub fn rule_category(&self, rule_id: &str) -> Option<RuleCategory> {
+        self.registry.category(rule_id)
+    }
+
+    /// List every rule ID this engine can report.
+    pub fn known_rule_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.registry.rules().iter().map(|r| r.id.clone()).collect();
+        ids.sort();
+        ids
+    }
+
     /// Analyze document content and return diagnostics.
     pub fn analyze(&self, content: &str, path: &Path) -> Vec<Diagnostic> {
+        self.analyze_in_crate(content, path, None)
+    }
+
+    /// Analyze a file of a crate and return diagnostics.
+    ///
+    /// With a crate index, the semantic analyzer traces calls into the
+    /// crate's other modules; diagnostics still only concern this file.
+    pub fn analyze_in_crate(
+        &self, content: &str, path: &Path, krate: Option<&CrateIndex>,
+    ) -> Vec<Diagnostic> {
         let mut diagnostics = Vec::new();
 
         // Only analyze Rust files
@@ -285,39 +216,51 @@ impl DiagnosticsEngine {
             return diagnostics;
         }
 
-        // Parse ignore directives first
-        let ignore_directives = parse_ignore_directives(content);
+        // Split, classify and parse the file once for every pass
+        let ctx = FileContext::new(content);
 
-        // Check for forbidden patterns
-        for (line_idx, line) in content.lines().enumerate() {
-            // Skip comments
-            let trimmed = line.trim();
-            if trimmed.starts_with("//") || trimmed.starts_with("/*") || trimmed.starts_with('*') {
-                continue;
+        // Parse ignore directives first, scoping them to the nodes they annotate
+        let mut ignore_directives = parse_directives(content, &ctx.tokens);
+        if let Some(file) = &ctx.ast {
+            resolve_directive_scopes(&mut ignore_directives, file, ctx.parse_error.is_some());
+        }
+        diagnostics.extend(self.check_directive_rules(&ctx, &ignore_directives));
+        diagnostics.extend(self.check_directive_policy(&ctx, &ignore_directives));
+
+        // Expired waivers no longer suppress anything
+        ignore_directives.retain(|d| !d.is_expired(&self.today));
+
+        // Names as written, so each entry of an expectation is checked alone
+        let listed: Vec<Option<HashSet<String>>> =
+            ignore_directives.iter().map(|d| d.rules.clone()).collect();
+
+        // Naming one rule of an alias group waives the whole group
+        for directive in &mut ignore_directives {
+            if let Some(rules) = &mut directive.rules {
+                self.registry.expand_aliases(rules);
             }
+        }
+
+        // Check for forbidden patterns
+        for (line_idx, &line) in ctx.lines.iter().enumerate() {
+            let in_code = |start: usize| ctx.is_code(line_idx, start);
 
             // Check forbidden patterns from constraints
-            for (pattern, regexes) in &self.compiled_patterns {
-                for regex in regexes {
-                    if let Some(mat) = regex.find(line) {
-                        let byte_offset = calculate_byte_offset(content, line_idx, mat.start());
-                        if is_inside_string_literal_at_offset(content, byte_offset) {
-                            continue;
-                        }
-                        diagnostics.push(self.create_forbidden_pattern_diagnostic(
-                            line_idx,
-                            mat.start(),
-                            mat.end(),
-                            pattern,
-                            line,
-                        ));
-                    }
+            for (idx, regex) in self.forbidden_patterns.matches(line) {
+                if let Some(mat) = regex.find_iter(line).find(|m| in_code(m.start())) {
+                    diagnostics.push(self.create_forbidden_pattern_diagnostic(
+                        line_idx,
+                        mat.start(),
+                        mat.end(),
+                        &self.context.forbidden_patterns[idx],
+                        line,
+                    ));
                 }
             }
 
             // Check for forbidden crates in use statements
             for cap in self.crate_use_pattern.captures_iter(line) {
-                if let Some(crate_match) = cap.get(1) {
+                if let Some(crate_match) = cap.get(1).filter(|m| in_code(m.start())) {
                     let crate_name = crate_match.as_str();
                     if self.context.is_forbidden_crate(crate_name) {
                         diagnostics.push(self.create_forbidden_crate_diagnostic(
@@ -333,7 +276,7 @@ impl DiagnosticsEngine {
 
             // Check for forbidden crates in extern crate
             for cap in self.crate_extern_pattern.captures_iter(line) {
-                if let Some(crate_match) = cap.get(1) {
+                if let Some(crate_match) = cap.get(1).filter(|m| in_code(m.start())) {
                     let crate_name = crate_match.as_str();
                     if self.context.is_forbidden_crate(crate_name) {
                         diagnostics.push(self.create_forbidden_crate_diagnostic(
@@ -348,32 +291,286 @@ impl DiagnosticsEngine {
             }
 
             // Check against comprehensive rule set
-            diagnostics.extend(self.check_rules(content, line, line_idx));
+            diagnostics.extend(self.check_rules(&ctx, line, line_idx));
         }
 
+        // Rules whose patterns span several lines
+        diagnostics.extend(self.check_scoped_rules(&ctx));
+
         // Perform semantic analysis (includes handler implementation checks)
-        let semantic_result = self.semantic_analyzer.analyze(content);
+        let semantic_result = match krate {
+            Some(krate) => self.semantic_analyzer.analyze_in_crate(&ctx, krate, path),
+            None => self.semantic_analyzer.analyze_context(&ctx),
+        };
         diagnostics.extend(semantic_result.diagnostics);
 
-        // Filter out ignored diagnostics
-        diagnostics.retain(|d| !should_ignore_diagnostic(d.line, &d.rule_id, &ignore_directives));
+        // Checks from different sources can find the same problem
+        let mut diagnostics = self.registry.dedupe(diagnostics);
+
+        // Filter out ignored diagnostics, remembering what each directive suppressed
+        let mut fired = vec![HashSet::new(); ignore_directives.len()];
+        diagnostics.retain(|d| {
+            let mut suppressed = false;
+            for (idx, directive) in ignore_directives.iter().enumerate() {
+                if directive.covers(d) {
+                    fired[idx].insert((d.rule_id.clone(), d.category));
+                    suppressed = true;
+                }
+            }
+            !suppressed
+        });
+
+        // Report directives that suppressed nothing (these can still be allowed)
+        diagnostics.extend(
+            self.check_unused_directives(&ctx, &ignore_directives, &listed, &fired)
+                .into_iter()
+                .filter(|d| !should_ignore_diagnostic(d, &ignore_directives)),
+        );
 
         diagnostics
     }
 
-    /// Check a line against the comprehensive rule set.
-    fn check_rules(&self, content: &str, line: &str, line_idx: usize) -> Vec<Diagnostic> {
+    /// Report directive entries that name no known rule, category or keyword.
+    fn check_directive_rules(
+        &self, ctx: &FileContext, directives: &[IgnoreDirective],
+    ) -> Vec<Diagnostic> {
+        if directives.iter().all(|d| d.rules.is_none()) {
+            return Vec::new();
+        }
+
+        let known_ids = self.known_rule_ids();
+        let mut candidates: Vec<&str> = DIRECTIVE_KEYWORDS.to_vec();
+        candidates.extend(RuleCategory::ALL.iter().map(|c| c.as_key()));
+        candidates.extend(known_ids.iter().map(String::as_str));
+
         let mut diagnostics = Vec::new();
 
-        for rule in &self.rule_set.rules {
-            if rule.is_anti_pattern
-                && let Some(mat) = rule.pattern.find(line)
-            {
-                let byte_offset = calculate_byte_offset(content, line_idx, mat.start());
-                if is_inside_string_literal_at_offset(content, byte_offset) {
-                    continue;
+        for directive in directives {
+            let Some(rules) = &directive.rules else { continue };
+            let line = ctx.line(directive.line - 1);
+
+            let mut unknown: Vec<&String> =
+                rules.iter().filter(|r| !candidates.contains(&r.as_str())).collect();
+            unknown.sort();
+
+            for name in unknown {
+                let column = directive_name_column(line, name);
+                let suggestion = closest_match(name, candidates.iter().copied());
+                let mut message =
+                    format!("unknown rule `{}` in {} directive", name, directive.attribute_path());
+                if let Some(s) = suggestion {
+                    message.push_str(&format!("\n\nDid you mean `{}`?", s));
                 }
+                diagnostics.push(Diagnostic {
+                    line: directive.line,
+                    column,
+                    end_column: column + name.len(),
+                    severity: RuleSeverity::Warning,
+                    rule_id: "unknown_lint".to_string(),
+                    rule_name: "Unknown Lint".to_string(),
+                    category: RuleCategory::Lint,
+                    message,
+                    fix_template: suggestion.map(String::from),
+                    source_snippet: Some(line.to_string()),
+                });
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Report directives that break the waiver policy.
+    ///
+    /// A directive whose `until` date has passed is an error, as is a
+    /// malformed date. When a reason is required, directives without one
+    /// are errors too.
+    fn check_directive_policy(
+        &self, ctx: &FileContext, directives: &[IgnoreDirective],
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
 
+        for directive in directives {
+            let line = ctx.line(directive.line - 1);
+            let (column, end_column) = directive_extent(ctx, directive);
+            let diagnostic =
+                |rule_id: &str, rule_name: &str, message: String, fix: &str| Diagnostic {
+                    line: directive.line,
+                    column,
+                    end_column,
+                    severity: RuleSeverity::Error,
+                    rule_id: rule_id.to_string(),
+                    rule_name: rule_name.to_string(),
+                    category: RuleCategory::Lint,
+                    message,
+                    fix_template: Some(fix.to_string()),
+                    source_snippet: Some(line.to_string()),
+                };
+
+            if let Some(until) = &directive.until {
+                if !suppression::is_valid_date(until) {
+                    diagnostics.push(diagnostic(
+                        "invalid_allow_directive",
+                        "Invalid Allow Directive",
+                        format!("`until = \"{}\"` is not a valid `YYYY-MM-DD` date", until),
+                        "Use a date such as `until = \"2026-12-31\"`",
+                    ));
+                } else if directive.is_expired(&self.today) {
+                    diagnostics.push(diagnostic(
+                        "expired_allow_directive",
+                        "Expired Allow Directive",
+                        format!(
+                            "{} directive expired on {} and no longer suppresses diagnostics.\n\nFix the underlying issue or renew the waiver with a new `until` date.",
+                            directive.attribute_path(),
+                            until
+                        ),
+                        "Fix the suppressed diagnostics and remove the directive",
+                    ));
+                }
+            }
+
+            if self.require_reason && directive.reason.is_none() {
+                diagnostics.push(diagnostic(
+                    "allow_without_reason",
+                    "Allow Without Reason",
+                    format!(
+                        "{} directive has no `reason`.\n\nEvery waiver must explain why the diagnostic does not apply.",
+                        directive.attribute_path()
+                    ),
+                    "Add `reason = \"...\"` to the directive",
+                ));
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Report directives that did not suppress any diagnostic.
+    ///
+    /// `allow` directives are reported as `unused_allow_directive` and
+    /// `expect` directives as `unfulfilled_lint_expectation`. An `expect`
+    /// directive listing several names is reported once for each known name
+    /// that suppressed nothing. Directives made up entirely of unknown names
+    /// are skipped, as they are already reported as `unknown_lint`.
+    ///
+    /// `listed` holds the names of each directive as written and `fired` the
+    /// rule and category of every diagnostic it suppressed.
+    fn check_unused_directives(
+        &self, ctx: &FileContext, directives: &[IgnoreDirective],
+        listed: &[Option<HashSet<String>>], fired: &[HashSet<(String, RuleCategory)>],
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        for ((directive, listed), fired) in directives.iter().zip(listed).zip(fired) {
+            if let Some(rules) = &directive.rules
+                && rules.iter().all(|r| !self.is_known_directive_name(r))
+            {
+                continue;
+            }
+
+            let line = ctx.line(directive.line - 1);
+            let (column, end_column) = directive_extent(ctx, directive);
+            let attribute = &line[column..end_column];
+            let report =
+                |rule_id: &str, rule_name: &str, message: String, fix: String| Diagnostic {
+                    line: directive.line,
+                    column,
+                    end_column,
+                    severity: RuleSeverity::Warning,
+                    rule_id: rule_id.to_string(),
+                    rule_name: rule_name.to_string(),
+                    category: RuleCategory::Lint,
+                    message,
+                    fix_template: Some(fix),
+                    source_snippet: Some(line.to_string()),
+                };
+
+            // Every rule an expectation lists must fire, not just one of them
+            if let Some(names) = listed.as_ref().filter(|names| names.len() > 1)
+                && directive.is_expectation
+            {
+                let mut unmet: Vec<&String> = names
+                    .iter()
+                    .filter(|name| self.is_known_directive_name(name))
+                    .filter(|name| !self.name_fired(name, fired))
+                    .collect();
+                unmet.sort();
+                diagnostics.extend(unmet.into_iter().map(|name| {
+                    report(
+                        "unfulfilled_lint_expectation",
+                        "Unfulfilled Lint Expectation",
+                        format!(
+                            "`{}` expected a `{}` diagnostic, but none was raised.\n\nThe expected issue has likely been fixed; remove `{}` from the directive.",
+                            attribute, name, name
+                        ),
+                        format!("Remove `{}` from `{}`", name, attribute),
+                    )
+                }));
+                continue;
+            }
+            if !fired.is_empty() {
+                continue;
+            }
+
+            let (rule_id, rule_name, message) = if directive.is_expectation {
+                (
+                    "unfulfilled_lint_expectation",
+                    "Unfulfilled Lint Expectation",
+                    format!(
+                        "`{}` expected a diagnostic, but none was raised.\n\nThe expected issue has likely been fixed; remove the directive.",
+                        attribute
+                    ),
+                )
+            } else {
+                (
+                    "unused_allow_directive",
+                    "Unused Allow Directive",
+                    format!(
+                        "`{}` does not suppress any diagnostic.\n\nThe code it covered has likely been fixed; remove the directive.",
+                        attribute
+                    ),
+                )
+            };
+
+            diagnostics.push(report(
+                rule_id,
+                rule_name,
+                message,
+                format!("Remove `{}`", attribute),
+            ));
+        }
+
+        diagnostics
+    }
+
+    /// Check whether a name listed in a directive covers one of the `fired`
+    /// rules, directly, through an alias, as their category or as `all`.
+    fn name_fired(&self, name: &str, fired: &HashSet<(String, RuleCategory)>) -> bool {
+        let aliases = self.registry.aliases(name);
+        fired.iter().any(|(rule_id, category)| {
+            DIRECTIVE_KEYWORDS.contains(&name)
+                || category.as_key() == name
+                || aliases.contains(&rule_id.as_str())
+        })
+    }
+
+    /// Check whether a directive entry names a rule, category or keyword.
+    fn is_known_directive_name(&self, name: &str) -> bool {
+        DIRECTIVE_KEYWORDS.contains(&name)
+            || RuleCategory::from_key(name).is_some()
+            || self.rule_category(name).is_some()
+    }
+
+    /// Check a line against the comprehensive rule set.
+    ///
+    /// The line is matched against all line-scoped rules at once; only the
+    /// rules that hit are located individually. Reports the first match of
+    /// each rule that starts in code, skipping strings and comments.
+    fn check_rules(&self, ctx: &FileContext, line: &str, line_idx: usize) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        for (idx, pattern) in self.rule_patterns.matches(line) {
+            let rule = &self.rule_set.rules[idx];
+            if let Some(mat) = pattern.find_iter(line).find(|m| ctx.is_code(line_idx, m.start())) {
                 let message = if let Some(fix) = rule.fix_template {
                     format!("{}\n\nSuggested fix: {}", rule.description, fix)
                 } else {
@@ -398,6 +595,54 @@ impl DiagnosticsEngine {
         diagnostics
     }
 
+    /// Check rules whose scope is wider than a line.
+    ///
+    /// Each rule's pattern runs over the statements it is scoped to. A match
+    /// is reported at the line and column where it starts; when nested
+    /// statements match the same text, only the innermost match is kept.
+    /// Without a parsed file, statements fall back to the whole file.
+    fn check_scoped_rules(&self, ctx: &FileContext) -> Vec<Diagnostic> {
+        let scoped: Vec<&Rule> = self
+            .rule_set
+            .rules
+            .iter()
+            .filter(|r| r.is_anti_pattern && r.scope != RuleScope::Line)
+            .collect();
+        if scoped.is_empty() {
+            return Vec::new();
+        }
+
+        let segments = ctx.ast.as_ref().map(ScopeSegments::collect);
+        let whole_file = 0..ctx.source.len();
+        let mut diagnostics = Vec::new();
+
+        for rule in scoped {
+            let ranges =
+                segments.as_ref().map_or(std::slice::from_ref(&whole_file), |s| &s.statements[..]);
+
+            // Innermost match per end offset
+            let mut matches: HashMap<usize, usize> = HashMap::new();
+            for range in ranges {
+                let Some(text) = ctx.source.get(range.clone()) else { continue };
+                for mat in rule.pattern.find_iter(text) {
+                    let (start, end) = (range.start + mat.start(), range.start + mat.end());
+                    if ctx.tokens.is_code(start) {
+                        let entry = matches.entry(end).or_insert(start);
+                        *entry = (*entry).max(start);
+                    }
+                }
+            }
+
+            let mut starts: Vec<usize> = matches.into_values().collect();
+            starts.sort_unstable();
+            starts.dedup_by_key(|start| ctx.line_col(*start).0);
+
+            diagnostics.extend(starts.into_iter().map(|start| rule_diagnostic(rule, ctx, start)));
+        }
+
+        diagnostics
+    }
+
     /// Create a diagnostic for a forbidden pattern.
     fn create_forbidden_pattern_diagnostic(
         &self, line_idx: usize, start: usize, end: usize, pattern: &ForbiddenPattern, line: &str,
@@ -447,6 +692,110 @@ impl Default for DiagnosticsEngine {
     }
 }
 
+/// Find the column of a rule name inside a directive's argument list.
+fn directive_name_column(line: &str, name: &str) -> usize {
+    let args_start = ["allow(", "allow-line(", "expect(", "expect-line("]
+        .iter()
+        .find_map(|open| line.find(open).map(|i| i + open.len()))
+        .unwrap_or(0);
+    line[args_start..].find(name).map_or(args_start, |i| args_start + i)
+}
+
+/// Byte range of a directive within its line: the attribute or the comment.
+fn directive_extent(ctx: &FileContext, directive: &IgnoreDirective) -> (usize, usize) {
+    let line_idx = directive.line - 1;
+    let line = ctx.line(line_idx);
+    match directive.form {
+        DirectiveForm::Attribute => {
+            // Skip `#` and `]` in comments on the same line
+            let in_code = |&i: &usize| ctx.is_code(line_idx, i);
+            let start = line.match_indices('#').map(|(i, _)| i).find(in_code).unwrap_or(0);
+            let end = line.rmatch_indices(']').map(|(i, _)| i).find(in_code);
+            (start, end.map_or(line.len(), |i| i + 1))
+        }
+        DirectiveForm::NextItemComment | DirectiveForm::LineComment => {
+            // The first `//` that opens a comment, not one in a string
+            let start = line
+                .match_indices("//")
+                .map(|(i, _)| i)
+                .find(|&i| ctx.tokens.kind_at(ctx.offset(line_idx, i)) == RegionKind::Comment)
+                .unwrap_or(0);
+            (start, line.trim_end().len())
+        }
+    }
+}
+
+/// A family of regexes matched against a line in a single pass.
+///
+/// The `RegexSet` tells which members match; each member's own regex is then
+/// used to locate the match. Members are keyed by an index into the owning
+/// collection, so one owner may contribute several regexes.
+struct PatternFamily {
+    set: RegexSet,
+    members: Vec<(usize, Regex)>,
+}
+
+impl PatternFamily {
+    fn new(members: Vec<(usize, Regex)>) -> Self {
+        let set = RegexSet::new(members.iter().map(|(_, regex)| regex.as_str()))
+            .expect("member patterns are valid regexes");
+        Self { set, members }
+    }
+
+    /// Members whose regex matches `line`, in declaration order.
+    fn matches<'a>(&'a self, line: &str) -> impl Iterator<Item = (usize, &'a Regex)> {
+        self.set.matches(line).into_iter().map(|i| {
+            let (idx, regex) = &self.members[i];
+            (*idx, regex)
+        })
+    }
+}
+
+/// Byte ranges of the statements in a file.
+#[derive(Default)]
+struct ScopeSegments {
+    statements: Vec<Range<usize>>,
+}
+
+impl ScopeSegments {
+    fn collect(file: &syn::File) -> Self {
+        let mut segments = Self::default();
+        segments.visit_file(file);
+        segments
+    }
+}
+
+impl<'ast> Visit<'ast> for ScopeSegments {
+    fn visit_stmt(&mut self, node: &'ast syn::Stmt) {
+        self.statements.push(node.span().byte_range());
+        syn::visit::visit_stmt(self, node);
+    }
+}
+
+/// Build the diagnostic for a scoped rule at a byte offset.
+fn rule_diagnostic(rule: &Rule, ctx: &FileContext, offset: usize) -> Diagnostic {
+    let (line_idx, column) = ctx.line_col(offset);
+    let line = ctx.line(line_idx);
+    let message = if let Some(fix) = rule.fix_template {
+        format!("{}\n\nSuggested fix: {}", rule.description, fix)
+    } else {
+        rule.description.to_string()
+    };
+
+    Diagnostic {
+        line: line_idx + 1,
+        column,
+        end_column: line.len().max(column),
+        severity: rule.severity,
+        rule_id: rule.id.to_string(),
+        rule_name: rule.name.to_string(),
+        category: rule.category,
+        message,
+        fix_template: rule.fix_template.map(String::from),
+        source_snippet: Some(line.to_string()),
+    }
+}
+
 /// Get the recommended alternative for a forbidden crate.
 fn get_crate_alternative(crate_name: &str) -> &'static str {
     match crate_name {
@@ -457,7 +806,7 @@ fn get_crate_alternative(crate_name: &str) -> &'static str {
         // Redis
         "redis" | "fred" => "Use the StateStore provider trait for caching",
         // Kafka/messaging
-        "rdkafka" | "kafka" | "lapin" | "amqp" => "Use the Publisher provider trait for messaging",
+        "rdkafka" | "kafka" | "lapin" | "amqp" => "Use the Publish provider trait for messaging",
         // Async runtimes
         "tokio" | "async-std" | "smol" | "actix-rt" | "futures-executor" => {
             "Use async/await without explicit runtime - WASI provides the executor"
@@ -629,64 +978,494 @@ fn main() {
     }
 
     #[test]
-    fn test_parse_ignore_directives() {
+    fn test_unknown_directive_rule_is_reported() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"
+#[omnia::allow(error_generic_unwarp)]
+fn main() {
+    let x = Some(5).unwrap();
+}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        let unknown: Vec<_> = diagnostics.iter().filter(|d| d.rule_id == "unknown_lint").collect();
+        assert_eq!(unknown.len(), 1);
+        assert_eq!((unknown[0].line, unknown[0].column), (2, 15));
+        assert!(unknown[0].message.contains("Did you mean `error_generic_unwrap`?"));
+        // The typo suppresses nothing
+        assert!(diagnostics.iter().any(|d| d.rule_id == "error_generic_unwrap"));
+    }
+
+    #[test]
+    fn test_known_directive_names_are_accepted() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"
+#[omnia::allow(error, forbidden_crate_tokio, unused_provider_bound)]
+fn main() {
+    let x = Some(5).unwrap();
+}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        assert!(!diagnostics.iter().any(|d| d.rule_id == "unknown_lint"), "{:?}", diagnostics);
+        // A category name suppresses every rule in the category
+        assert!(!diagnostics.iter().any(|d| d.rule_id == "error_generic_unwrap"));
+    }
+
+    #[test]
+    fn test_unused_directive_is_reported() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"
+#[omnia::allow(error_generic_unwrap)]
+fn main() {
+    let x = Some(5).unwrap_or(0);
+}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        let unused: Vec<_> =
+            diagnostics.iter().filter(|d| d.rule_id == "unused_allow_directive").collect();
+        assert_eq!(unused.len(), 1);
+        assert_eq!(unused[0].line, 2);
+        assert_eq!(
+            unused[0].fix_template.as_deref(),
+            Some("Remove `#[omnia::allow(error_generic_unwrap)]`")
+        );
+    }
+
+    #[test]
+    fn test_used_directive_is_not_reported() {
+        let engine = DiagnosticsEngine::new();
         let content = r#"
-#![omnia::allow(all)]
-#[omnia::allow(unwrap_used)]
-fn foo() {}
-#[omnia::allow(rule1, rule2)]
-fn bar() {}
+#[omnia::allow(error_generic_unwrap)]
+fn main() {
+    let x = Some(5).unwrap();
+}
 "#;
-        let directives = parse_ignore_directives(content);
-        assert_eq!(directives.len(), 3);
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        assert!(!diagnostics.iter().any(|d| d.rule_id == "unused_allow_directive"));
+    }
 
-        // First directive is file-level, ignores all
-        assert!(directives[0].is_file_level);
-        assert!(directives[0].rules.is_none());
+    #[test]
+    fn test_directive_covers_long_function() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"
+#[omnia::allow(error_generic_unwrap)]
+fn long() {
+    let a = 1;
+    let b = 2;
+    let c = 3;
+    let d = 4;
+    let e = 5;
+    let f = 6;
+    let g = 7;
+    let h = 8;
+    let x = Some(5).unwrap();
+}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        assert!(!diagnostics.iter().any(|d| d.rule_id == "error_generic_unwrap"));
+    }
 
-        // Second directive is line-level, ignores specific rule
-        assert!(!directives[1].is_file_level);
-        assert!(directives[1].rules.as_ref().unwrap().contains("unwrap_used"));
+    #[test]
+    fn test_directive_does_not_cover_following_item() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"
+#[omnia::allow(error_generic_unwrap)]
+fn first() {}
+fn second() {
+    let x = Some(5).unwrap();
+}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        assert!(diagnostics.iter().any(|d| d.rule_id == "error_generic_unwrap" && d.line == 5));
+    }
+
+    #[test]
+    fn test_directive_on_nested_item() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"
+impl Foo {
+    #[omnia::allow(error_generic_unwrap)]
+    fn allowed() {
+        let x = Some(5).unwrap();
+    }
 
-        // Third directive ignores multiple rules
-        assert!(!directives[2].is_file_level);
-        let rules = directives[2].rules.as_ref().unwrap();
-        assert!(rules.contains("rule1"));
-        assert!(rules.contains("rule2"));
+    fn checked() {
+        let y = Some(6).unwrap();
+    }
+}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        let lines: Vec<usize> = diagnostics
+            .iter()
+            .filter(|d| d.rule_id == "error_generic_unwrap")
+            .map(|d| d.line)
+            .collect();
+        assert_eq!(lines, vec![9]);
     }
 
     #[test]
-    fn test_is_inside_string_literal_regular() {
-        let content = r#"let x = "hello.unwrap()"; let y = foo.unwrap();"#;
-        // offset inside the string literal
-        let offset_in_str = content.find("hello").unwrap();
-        assert!(is_inside_string_literal_at_offset(content, offset_in_str));
+    fn test_directive_window_used_when_file_does_not_parse() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"
+#[omnia::allow(error_generic_unwrap)]
+fn broken( {
+    let x = Some(5).unwrap();
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        assert!(!diagnostics.iter().any(|d| d.rule_id == "error_generic_unwrap"));
+    }
 
-        // offset outside the string literal (the second unwrap)
-        let second_unwrap = content.rfind("unwrap").unwrap();
-        assert!(!is_inside_string_literal_at_offset(content, second_unwrap));
+    #[test]
+    fn test_directive_window_used_for_unrecovered_item() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"
+fn valid() -> u32 {
+    1
+}
+
+#[omnia::allow(error_generic_unwrap)]
+fn broken(x: u32 {
+    let a = Some(5).unwrap();
+    let b = Some(x).unwrap();
+}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        assert!(diagnostics.iter().any(|d| d.rule_id == "parse_error"));
+        assert!(!diagnostics.iter().any(|d| d.rule_id == "error_generic_unwrap"));
+        assert!(!diagnostics.iter().any(|d| d.rule_id == "unused_allow_directive"));
+    }
+
+    #[test]
+    fn test_expired_directive_is_reported_and_ignored() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"
+#[omnia::allow(error_generic_unwrap, reason = "temporary", until = "2000-01-01")]
+fn main() {
+    let x = Some(5).unwrap();
+}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        let expired = diagnostics.iter().find(|d| d.rule_id == "expired_allow_directive").unwrap();
+        assert_eq!(expired.line, 2);
+        assert_eq!(expired.severity, RuleSeverity::Error);
+        assert!(diagnostics.iter().any(|d| d.rule_id == "error_generic_unwrap"));
+        assert!(!diagnostics.iter().any(|d| d.rule_id == "unused_allow_directive"));
+    }
+
+    #[test]
+    fn test_future_until_still_suppresses() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"
+#[omnia::allow(error_generic_unwrap, until = "2999-12-31")]
+fn main() {
+    let x = Some(5).unwrap();
+}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    }
+
+    #[test]
+    fn test_invalid_until_is_reported() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"
+#[omnia::allow(error_generic_unwrap, until = "31/12/2026")]
+fn main() {
+    let x = Some(5).unwrap();
+}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        assert!(diagnostics.iter().any(|d| d.rule_id == "invalid_allow_directive"));
     }
 
     #[test]
-    fn test_is_inside_string_literal_raw_string() {
-        let content = r##"let x = r#"hello.unwrap()"#; let y = foo.unwrap();"##;
-        let in_raw = content.find("hello").unwrap();
-        assert!(is_inside_string_literal_at_offset(content, in_raw));
+    fn test_require_reason() {
+        let content = r#"
+#[omnia::allow(error_generic_unwrap)]
+fn first() {
+    let x = Some(5).unwrap();
+}
+
+#[omnia::allow(error_generic_unwrap, reason = "checked above")]
+fn second() {
+    let y = Some(6).unwrap();
+}
+"#;
+        let lax = DiagnosticsEngine::new().analyze(content, Path::new("test.rs"));
+        assert!(lax.is_empty(), "{:?}", lax);
+
+        let strict = DiagnosticsEngine::new()
+            .with_require_reason(true)
+            .analyze(content, Path::new("test.rs"));
+        let lines: Vec<usize> =
+            strict.iter().filter(|d| d.rule_id == "allow_without_reason").map(|d| d.line).collect();
+        assert_eq!(lines, vec![2]);
     }
 
     #[test]
-    fn test_is_inside_string_literal_comment() {
-        let content = "// let x = foo.unwrap();\nlet y = bar.unwrap();";
-        // Inside a comment line - the function skips comments
-        let in_comment = content.find("foo").unwrap();
-        assert!(!is_inside_string_literal_at_offset(content, in_comment));
+    fn test_comment_directives_suppress() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"
+// omnia-lint: allow(error_generic_unwrap) -- validated by the caller
+fn first() {
+    let x = Some(5).unwrap();
+}
+
+fn second() {
+    let y = Some(6).unwrap(); // omnia-lint: allow-line(error_generic_unwrap)
+    let z = Some(7).unwrap();
+}
+
+#[cfg_attr(omnia_lint, omnia::allow(error_generic_unwrap))]
+fn third() {
+    let w = Some(8).unwrap();
+}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        let lines: Vec<usize> = diagnostics.iter().map(|d| d.line).collect();
+        assert_eq!(lines, vec![9], "{:?}", diagnostics);
     }
 
     #[test]
-    fn test_calculate_byte_offset() {
-        let content = "line0\nline1\nline2";
-        assert_eq!(calculate_byte_offset(content, 0, 0), 0);
-        assert_eq!(calculate_byte_offset(content, 1, 0), 6);
-        assert_eq!(calculate_byte_offset(content, 2, 3), 15);
+    fn test_unused_comment_directive_is_reported() {
+        let engine = DiagnosticsEngine::new();
+        let content = "fn main() {} // omnia-lint: allow-line(error_generic_unwrap)\n";
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule_id, "unused_allow_directive");
+        assert_eq!(diagnostics[0].column, 13);
+        assert_eq!(
+            diagnostics[0].fix_template.as_deref(),
+            Some("Remove `// omnia-lint: allow-line(error_generic_unwrap)`")
+        );
     }
+
+    #[test]
+    fn test_attribute_in_doc_comment_does_not_suppress() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"/// Example: #[omnia::allow(all)]
+fn f() {
+    std::fs::read("a");
+}
+
+// #[omnia::allow(wasm)]
+fn g() {}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        assert!(diagnostics.iter().any(|d| d.line == 3 && d.category == RuleCategory::Wasm));
+        assert!(
+            !diagnostics.iter().any(|d| d.rule_id == "unused_allow_directive"),
+            "{:?}",
+            diagnostics
+        );
+    }
+
+    #[test]
+    fn test_comment_directive_in_string_does_not_suppress() {
+        let engine = DiagnosticsEngine::new();
+        let content = "fn main() {\n    let s = \"// omnia-lint: allow-line(error_generic_unwrap)\"; x.unwrap(); // omnia-lint: allow-line(std_fs)\n}\n";
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        let found: Vec<(&str, usize)> =
+            diagnostics.iter().map(|d| (d.rule_id.as_str(), d.column)).collect();
+        assert_eq!(
+            found,
+            vec![("error_generic_unwrap", 64), ("unused_allow_directive", 75)],
+            "{:?}",
+            diagnostics
+        );
+    }
+
+    #[test]
+    fn test_unfulfilled_expectation_is_reported() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"
+#[omnia::expect(error_generic_unwrap)]
+fn fulfilled() {
+    let x = Some(5).unwrap();
+}
+
+#[omnia::expect(error_generic_unwrap)]
+fn unfulfilled() {
+    let y = 6;
+}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        assert_eq!(diagnostics[0].rule_id, "unfulfilled_lint_expectation");
+        assert_eq!(diagnostics[0].line, 7);
+        assert_eq!(diagnostics[0].severity, RuleSeverity::Warning);
+    }
+
+    #[test]
+    fn test_unfulfilled_expectation_per_rule() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"
+#[omnia::expect(error_generic_unwrap, std_fs, error_panic_macro)]
+fn partly() {
+    let x = Some(5).unwrap();
+}
+
+#[omnia::expect(wasm_std_fs, error)]
+fn through_alias_and_category() {
+    std::fs::read("a");
+    let y = Some(6).expect("y");
+}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        let unfulfilled: Vec<(usize, &str)> = diagnostics
+            .iter()
+            .filter(|d| d.rule_id == "unfulfilled_lint_expectation")
+            .map(|d| (d.line, d.fix_template.as_deref().unwrap_or("")))
+            .collect();
+        let attribute = "#[omnia::expect(error_generic_unwrap, std_fs, error_panic_macro)]";
+        assert_eq!(
+            unfulfilled,
+            vec![
+                (2, format!("Remove `error_panic_macro` from `{}`", attribute).as_str()),
+                (2, format!("Remove `std_fs` from `{}`", attribute).as_str()),
+            ],
+            "{:?}",
+            diagnostics
+        );
+    }
+
+    #[test]
+    fn test_unused_directive_can_be_allowed() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"#![omnia::allow(unused_allow_directive)]
+
+#[omnia::allow(error_panic_macro)]
+fn other() {}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    }
+
+    #[test]
+    fn test_strings_and_comments_are_skipped() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"
+/* outer
+   /* nested */
+   let a = foo.unwrap();
+*/
+/// Never call `bar.unwrap()` here.
+fn main() {
+    let quote = '"'; let b = baz.unwrap(); let msg = "qux.unwrap()";
+}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        let unwraps: Vec<(usize, usize)> = diagnostics
+            .iter()
+            .filter(|d| d.rule_id == "error_generic_unwrap")
+            .map(|d| (d.line, d.column))
+            .collect();
+        assert_eq!(unwraps, vec![(8, 32)]);
+    }
+
+    #[test]
+    fn test_statement_scoped_rules_match_across_lines() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"
+async fn handle(ctx: &Context, items: Vec<Item>, kind: String) -> Result<()> {
+    for item in &items {
+        let owned = item.name.clone();
+        let label = format!("{}", owned);
+    }
+
+    match kind.as_str() {
+        "a" => {}
+        _ => {}
+    }
+
+    ctx.provider
+        .set(&key, &value, None)
+        .await?;
+    Ok(())
+}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        let found = |rule_id: &str| -> Vec<(usize, usize)> {
+            diagnostics
+                .iter()
+                .filter(|d| d.rule_id == rule_id)
+                .map(|d| (d.line, d.column))
+                .collect()
+        };
+        assert_eq!(found("perf_clone_in_loop"), vec![(3, 4)]);
+        assert_eq!(found("perf_format_in_loop"), vec![(3, 4)]);
+        assert_eq!(found("type_string_match"), vec![(8, 4)]);
+        assert_eq!(found("cache_missing_ttl"), vec![(13, 4)]);
+    }
+
+    #[test]
+    fn test_statement_scoped_rules_report_innermost_match() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"
+fn main() {
+    for row in &rows {
+        for cell in row {
+            let owned = cell.clone();
+        }
+    }
+}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        let lines: Vec<usize> = diagnostics
+            .iter()
+            .filter(|d| d.rule_id == "perf_clone_in_loop")
+            .map(|d| d.line)
+            .collect();
+        assert_eq!(lines, vec![4]);
+    }
+
+    #[test]
+    fn test_rule_category_covers_all_sources() {
+        let engine = DiagnosticsEngine::new();
+        assert_eq!(engine.rule_category("error_generic_unwrap"), Some(RuleCategory::Error));
+        assert_eq!(engine.rule_category("std_fs"), Some(RuleCategory::Wasm));
+        assert_eq!(engine.rule_category("forbidden_crate_tokio"), Some(RuleCategory::Wasm));
+        assert_eq!(engine.rule_category("unused_provider_bound"), Some(RuleCategory::Provider));
+        assert_eq!(engine.rule_category("forbidden_crate_serde"), None);
+        assert_eq!(engine.rule_category("error_generic_unwarp"), None);
+    }
+
+    #[test]
+    fn test_overlapping_checks_report_once() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"
+use tokio::runtime;
+
+fn read() {
+    let data = std::fs::read("x");
+    let c = "statestore";
+    println!("{:?}", data);
+}
+
+async fn cache(ctx: Ctx) {
+    ctx.provider.set("k", v, None).await;
+}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        let hits = |line: usize| -> Vec<&str> {
+            diagnostics.iter().filter(|d| d.line == line).map(|d| d.rule_id.as_str()).collect()
+        };
+        assert_eq!(hits(2), vec!["forbidden_crate_tokio"]);
+        assert_eq!(hits(5), vec!["wasm_std_fs"]);
+        assert_eq!(hits(7), vec!["println_debug"]);
+        assert_eq!(hits(11), vec!["cache_missing_ttl"]);
+    }
+
+    #[test]
+    fn test_allowing_an_alias_waives_the_finding() {
+        let engine = DiagnosticsEngine::new();
+        let content = r#"
+#[omnia::allow(std_fs, reason = "host-only tool")]
+fn read() {
+    let data = std::fs::read("x");
+}
+"#;
+        let diagnostics = engine.analyze(content, Path::new("test.rs"));
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    }
+
 }
@@ -19,17 +19,29 @@
 
 pub mod config;
 pub mod constraints;
+pub mod context;
+pub mod crate_index;
 pub mod diagnostics;
 pub mod output;
+pub mod providers;
+pub mod registry;
 pub mod rules;
 pub mod semantic;
+pub mod suppression;
+pub mod tokens;
 
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
 
 use anyhow::Result;
 pub use config::CargoLintConfig;
+pub use context::FileContext;
+pub use crate_index::CrateIndex;
 pub use diagnostics::{Diagnostic, DiagnosticsEngine, IgnoreDirective, parse_ignore_directives};
-pub use rules::{LintLevel, Rule, RuleCategory, RuleSet, RuleSeverity};
+pub use providers::{ProviderCatalog, ProviderMethod, ProviderTrait};
+pub use registry::{RuleRegistry, RuleSource};
+pub use rules::{LintLevel, Rule, RuleCategory, RuleScope, RuleSet, RuleSeverity};
 
 /// Configuration for the linter.
 #[derive(Debug, Clone)]
@@ -51,6 +63,10 @@ pub struct LintConfig {
 
     /// Cargo.toml-based severity overrides (populated from `[lints.omnia]`).
     pub cargo_overrides: CargoLintConfig,
+
+    /// Require a `reason = "..."` on every `omnia::allow` directive, in
+    /// addition to `metadata.omnia.require_reason` in `Cargo.toml`.
+    pub require_reason: bool,
 }
 
 impl Default for LintConfig {
@@ -62,6 +78,7 @@ impl Default for LintConfig {
             min_severity: RuleSeverity::Hint,
             show_fixes: true,
             cargo_overrides: CargoLintConfig::default(),
+            require_reason: false,
         }
     }
 }
@@ -70,26 +87,56 @@ impl Default for LintConfig {
 pub struct Linter {
     engine: DiagnosticsEngine,
     config: LintConfig,
+
+    /// Crate indexes by crate root, shared by the files of each crate.
+    crates: Mutex<HashMap<PathBuf, Arc<CrateIndex>>>,
 }
 
 impl Linter {
     /// Create a new linter with the given configuration.
     pub fn new(config: LintConfig) -> Self {
+        let mut providers = ProviderCatalog::new();
+        providers.extend(config.cargo_overrides.providers.iter().cloned());
+        let require_reason =
+            config.require_reason || config.cargo_overrides.require_reason.unwrap_or(false);
+
         Self {
-            engine: DiagnosticsEngine::new(),
+            engine: DiagnosticsEngine::new()
+                .with_require_reason(require_reason)
+                .with_provider_catalog(providers),
             config,
+            crates: Mutex::new(HashMap::new()),
         }
     }
 
     /// Lint a single file and return diagnostics.
+    ///
+    /// When the file belongs to a crate's module tree, calls into the
+    /// crate's other modules are followed by the semantic analysis.
     pub fn lint_file<P: AsRef<Path>>(&self, path: P) -> Result<Vec<Diagnostic>> {
         let path = path.as_ref();
         let content = std::fs::read_to_string(path)?;
-        let diagnostics = self.engine.analyze(&content, path);
+        let krate = self.crate_index(path);
+        let diagnostics = self.engine.analyze_in_crate(&content, path, krate.as_deref());
 
         Ok(self.filter_diagnostics(diagnostics))
     }
 
+    /// Index of the crate whose module tree contains `path`, loaded once
+    /// per crate root.
+    fn crate_index(&self, path: &Path) -> Option<Arc<CrateIndex>> {
+        let mut crates = self.crates.lock().unwrap_or_else(PoisonError::into_inner);
+        for root in crate_index::crate_roots(path) {
+            let krate = crates.entry(root.clone()).or_insert_with(|| {
+                Arc::new(CrateIndex::load(&root, self.engine.provider_catalog()))
+            });
+            if krate.contains(path) {
+                return Some(Arc::clone(krate));
+            }
+        }
+        None
+    }
+
     /// Lint a string of content.
     pub fn lint_str(&self, content: &str, filename: &str) -> Vec<Diagnostic> {
         let path = Path::new(filename);
@@ -97,14 +144,37 @@ impl Linter {
         self.filter_diagnostics(diagnostics)
     }
 
+    /// The `Cargo.toml` the `[lints.omnia]` configuration was loaded from.
+    pub fn config_source(&self) -> Option<&Path> {
+        self.config.cargo_overrides.source.as_deref()
+    }
+
+    /// Validate the `[lints.omnia]` tables of the loaded `Cargo.toml`.
+    ///
+    /// Returns an empty list when no `Cargo.toml` was loaded.
+    pub fn lint_config(&self) -> Result<Vec<Diagnostic>> {
+        let Some(source) = &self.config.cargo_overrides.source else {
+            return Ok(Vec::new());
+        };
+        let content = std::fs::read_to_string(source)?;
+        let diagnostics = config::validate_lint_tables(&content, &self.engine.known_rule_ids());
+
+        Ok(self.filter_diagnostics(diagnostics))
+    }
+
     /// Filter diagnostics based on configuration.
     fn filter_diagnostics(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
         diagnostics
             .into_iter()
             .filter_map(|mut d| {
+                // Settings for any alias of the rule apply to it
+                let aliases = self.engine.registry().aliases(&d.rule_id);
+                let aliases: Vec<&str> =
+                    std::iter::once(d.rule_id.as_str()).chain(aliases).collect();
+
                 if !self.config.cargo_overrides.is_empty()
                     && let Some(level) =
-                        self.config.cargo_overrides.effective_level(&d.rule_id, d.category)
+                        self.config.cargo_overrides.effective_level_any(&aliases, d.category)
                 {
                     match level.to_severity() {
                         None => return None,
@@ -118,7 +188,7 @@ impl Linter {
                 }
 
                 // Filter by disabled rules (CLI --disable flag)
-                if self.config.disabled_rules.contains(&d.rule_id) {
+                if aliases.iter().any(|id| self.config.disabled_rules.iter().any(|r| r == id)) {
                     return None;
                 }
 
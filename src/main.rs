@@ -1,12 +1,13 @@
 //! Omnia Lint CLI - Command-line interface for the Omnia linter.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 use colored::Colorize;
+use omnia_lint::config::LevelScope;
 use omnia_lint::output::{DiagnosticSummary, OutputFormat, format_diagnostics, format_json_all};
-use omnia_lint::{LintConfig, Linter, RuleCategory, RuleSeverity, config};
+use omnia_lint::{DiagnosticsEngine, LintConfig, Linter, RuleCategory, RuleSeverity, config};
 use rayon::prelude::*;
 use walkdir::WalkDir;
 
@@ -16,7 +17,11 @@ use walkdir::WalkDir;
 #[command(author = "Augentic Team")]
 #[command(version = "0.1.0")]
 #[command(about = "Lint Rust code for Omnia WASM32 handler compliance", long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Files or directories to lint
     #[arg(required = true)]
     paths: Vec<PathBuf>,
@@ -56,6 +61,32 @@ struct Args {
     /// Maximum number of diagnostics to show (0 for unlimited)
     #[arg(long, default_value = "0")]
     max_diagnostics: usize,
+
+    /// Require a `reason = "..."` on every omnia::allow directive
+    #[arg(long)]
+    require_reason: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Inspect the `[lints.omnia]` configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommand {
+    /// Show how the effective level of a rule is resolved
+    Explain {
+        /// Rule ID to explain
+        rule: String,
+
+        /// File or directory whose Cargo.toml configuration is used
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -93,6 +124,7 @@ enum CategoryArg {
     Caching,
     Time,
     Auth,
+    Lint,
 }
 
 impl From<OutputFormatArg> for OutputFormat {
@@ -133,13 +165,94 @@ impl From<CategoryArg> for RuleCategory {
             CategoryArg::Caching => RuleCategory::Caching,
             CategoryArg::Time => RuleCategory::Time,
             CategoryArg::Auth => RuleCategory::Auth,
+            CategoryArg::Lint => RuleCategory::Lint,
         }
     }
 }
 
+/// Print the resolution chain for a rule's effective level.
+fn explain_rule(rule_id: &str, path: &Path) -> ExitCode {
+    let engine = DiagnosticsEngine::new();
+    let Some(category) = engine.rule_category(rule_id) else {
+        eprintln!("{} unknown rule `{}`", "error:".red().bold(), rule_id);
+        return ExitCode::from(2);
+    };
+
+    let start =
+        if path.is_file() { path.parent().unwrap_or(path).to_path_buf() } else { path.to_path_buf() };
+    let cfg = match config::discover_config(&start) {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            eprintln!("{} Failed to load Cargo.toml config: {}", "error:".red().bold(), e);
+            return ExitCode::from(2);
+        }
+    };
+
+    println!("{} {} (category `{}`)", "rule:".bold(), rule_id.cyan(), category.as_key());
+    println!("{} {}", "file:".bold(), path.display());
+    match &cfg.source {
+        Some(src) => println!("{} {}", "config:".bold(), src.display()),
+        None => println!("{} no Cargo.toml found", "config:".bold()),
+    }
+    println!();
+
+    // Settings for any alias of the rule apply to it
+    let aliases = engine.registry().aliases(rule_id);
+    let aliases: Vec<&str> = std::iter::once(rule_id).chain(aliases).collect();
+    let candidates = cfg.explain(&aliases, category);
+    if candidates.is_empty() {
+        println!("  no `[lints.omnia]` entry applies; the rule's built-in severity is used");
+        return ExitCode::SUCCESS;
+    }
+
+    for candidate in &candidates {
+        let tier = match candidate.scope {
+            LevelScope::Rule => "rule",
+            LevelScope::Alias => "alias",
+            LevelScope::Category => "category",
+            LevelScope::All => "all",
+        };
+        let entry = candidate.entry;
+        let line = format!("{} {} = \"{}\"", entry.table.header(), entry.key, entry.level.as_str());
+        let source = entry.source.as_ref().map(|s| s.display().to_string()).unwrap_or_default();
+        if candidate.wins {
+            println!("  {} {:<10} {}  {}", "→".green().bold(), tier, line.green(), source.dimmed());
+        } else {
+            println!(
+                "    {:<10} {}  {} {}",
+                tier,
+                line.dimmed(),
+                source.dimmed(),
+                "(shadowed)".dimmed()
+            );
+        }
+    }
+
+    let winner = candidates[0].entry.level;
+    println!();
+    match winner.to_severity() {
+        None => println!("{} allow (diagnostics are suppressed)", "effective level:".bold()),
+        Some(sev) => println!(
+            "{} {} (reported as {})",
+            "effective level:".bold(),
+            winner.as_str(),
+            format!("{sev:?}").to_lowercase()
+        ),
+    }
+
+    ExitCode::SUCCESS
+}
+
 fn main() -> ExitCode {
     let args = Args::parse();
 
+    if let Some(Command::Config {
+        action: ConfigCommand::Explain { rule, path },
+    }) = &args.command
+    {
+        return explain_rule(rule, path);
+    }
+
     // Discover Cargo.toml configuration from the first path argument
     let cargo_overrides = args
         .paths
@@ -148,18 +261,17 @@ fn main() -> ExitCode {
             let start = if p.is_file() { p.parent().unwrap_or(p).to_path_buf() } else { p.clone() };
             match config::discover_config(&start) {
                 Ok(cfg) => {
-                    if !cfg.is_empty() {
-                        if let Some(ref src) = cfg.source {
-                            eprintln!(
-                                "{} Loaded omnia lint config from {}",
-                                "note:".blue().bold(),
-                                src.display()
-                            );
-                        }
-                        Some(cfg)
-                    } else {
-                        None
+                    if !cfg.is_empty()
+                        && let Some(ref src) = cfg.source
+                    {
+                        eprintln!(
+                            "{} Loaded omnia lint config from {}",
+                            "note:".blue().bold(),
+                            src.display()
+                        );
                     }
+                    // Kept even when empty so the tables can still be validated
+                    Some(cfg)
                 }
                 Err(e) => {
                     eprintln!(
@@ -184,6 +296,7 @@ fn main() -> ExitCode {
         min_severity: args.severity.into(),
         show_fixes: args.show_fixes,
         cargo_overrides,
+        require_reason: args.require_reason,
     };
 
     let linter = Linter::new(config);
@@ -212,7 +325,7 @@ fn main() -> ExitCode {
     }
 
     // Lint files in parallel
-    let results: Vec<_> = files
+    let mut results: Vec<_> = files
         .par_iter()
         .filter_map(|file| match linter.lint_file(file) {
             Ok(diagnostics) => Some((file.clone(), diagnostics)),
@@ -223,6 +336,17 @@ fn main() -> ExitCode {
         })
         .collect();
 
+    // Report problems in the [lints.omnia] tables alongside the source files
+    if let Some(source) = linter.config_source() {
+        match linter.lint_config() {
+            Ok(diagnostics) if !diagnostics.is_empty() => {
+                results.insert(0, (source.to_path_buf(), diagnostics));
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("{}: {} - {}", "Error".red().bold(), source.display(), e),
+        }
+    }
+
     // Flatten all diagnostics for summary, truncation, and JSON output
     let all_diagnostics: Vec<(&PathBuf, &omnia_lint::Diagnostic)> = results
         .iter()
@@ -304,7 +428,7 @@ fn main() -> ExitCode {
         println!();
         println!("{}", "Rule Statistics:".bold().underline());
         let mut sorted_rules: Vec<_> = rule_counts.into_iter().collect();
-        sorted_rules.sort_by(|a, b| b.1.cmp(&a.1));
+        sorted_rules.sort_by_key(|r| std::cmp::Reverse(r.1));
         for (rule_id, count) in sorted_rules.iter().take(10) {
             println!("  {:40} {}", rule_id, count);
         }
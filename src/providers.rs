@@ -0,0 +1,404 @@
+//! Catalog of the provider traits the semantic analysis knows.
+//!
+//! Bound analysis needs to know which traits are provider capabilities and
+//! which trait a call such as `provider.send(..)` belongs to. Each entry
+//! names a trait, the crate it comes from and its methods, with the number
+//! of arguments, how the receiver is taken, whether the method is async and,
+//! optionally, the type it resolves to.
+//! The catalog ships with the Omnia SDK traits and can be extended from
+//! `Cargo.toml`, so in-house provider traits get the same analysis:
+//!
+//! ```toml
+//! [package.metadata.omnia.providers.BlobStore]
+//! crate = "my_sdk"
+//! methods = [
+//!     { name = "put", arity = 2 },
+//!     { name = "get_blob", arity = 1, receiver = "&self", async = true },
+//!     { name = "list", returns = "Vec<String>" },
+//! ]
+//! ```
+
+use std::collections::HashSet;
+
+/// How a provider method takes its receiver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Receiver {
+    /// `&self`
+    Ref,
+    /// `&mut self`
+    RefMut,
+    /// `self`
+    Value,
+    /// No receiver; only callable as `Trait::method(..)`.
+    None,
+}
+
+impl Receiver {
+    /// Parse a receiver as written in configuration.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "&self" => Some(Receiver::Ref),
+            "&mut self" => Some(Receiver::RefMut),
+            "self" => Some(Receiver::Value),
+            "none" => Some(Receiver::None),
+            _ => None,
+        }
+    }
+}
+
+/// A method of a provider trait.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderMethod {
+    /// Method name.
+    pub name: String,
+
+    /// Number of arguments, not counting the receiver.
+    pub arity: usize,
+
+    /// How the receiver is taken.
+    pub receiver: Receiver,
+
+    /// Whether the method returns a future to `.await`.
+    pub is_async: bool,
+
+    /// The type the method resolves to, without the `Result`, such as
+    /// `Option<Vec<u8>>`, if known.
+    pub returns: Option<String>,
+}
+
+impl ProviderMethod {
+    /// An async `&self` method.
+    pub fn new(name: &str, arity: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            arity,
+            receiver: Receiver::Ref,
+            is_async: true,
+            returns: None,
+        }
+    }
+
+    /// Make the method synchronous.
+    pub fn sync(mut self) -> Self {
+        self.is_async = false;
+        self
+    }
+
+    /// Set the type the method resolves to.
+    pub fn returns(mut self, ty: &str) -> Self {
+        self.returns = Some(ty.to_string());
+        self
+    }
+
+    /// Whether the method can resolve to a type named `name`: the last path
+    /// segment of the type before any generic arguments, so `Option` for
+    /// `Option<Vec<u8>>`. A method of unknown type can resolve to any.
+    fn returns_type(&self, name: &str) -> bool {
+        self.returns.as_deref().is_none_or(|ty| {
+            let base = ty.split('<').next().unwrap_or(ty).trim();
+            base.rsplit("::").next() == Some(name)
+        })
+    }
+}
+
+/// A provider trait and its methods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderTrait {
+    /// Trait name, as used in bounds.
+    pub name: String,
+
+    /// Path of the crate that defines the trait, e.g. `omnia_sdk`.
+    pub crate_path: String,
+
+    /// Methods called on providers.
+    pub methods: Vec<ProviderMethod>,
+}
+
+impl ProviderTrait {
+    /// A trait of `crate_path` with the given methods.
+    pub fn new(name: &str, crate_path: &str, methods: Vec<ProviderMethod>) -> Self {
+        Self {
+            name: name.to_string(),
+            crate_path: crate_path.to_string(),
+            methods,
+        }
+    }
+}
+
+/// The provider traits known to the semantic analysis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderCatalog {
+    traits: Vec<ProviderTrait>,
+}
+
+impl ProviderCatalog {
+    /// The Omnia SDK provider traits.
+    pub fn new() -> Self {
+        const SDK: &str = "omnia_sdk";
+        Self {
+            traits: vec![
+                ProviderTrait::new(
+                    "Config",
+                    SDK,
+                    vec![ProviderMethod::new("get", 1).returns("String")],
+                ),
+                ProviderTrait::new("HttpRequest", SDK, vec![ProviderMethod::new("fetch", 1)]),
+                ProviderTrait::new("Publish", SDK, vec![ProviderMethod::new("send", 2)]),
+                ProviderTrait::new(
+                    "StateStore",
+                    SDK,
+                    vec![
+                        ProviderMethod::new("get", 1).returns("Option<Vec<u8>>"),
+                        ProviderMethod::new("set", 3),
+                        ProviderMethod::new("delete", 1),
+                    ],
+                ),
+                ProviderTrait::new("Identity", SDK, vec![ProviderMethod::new("access_token", 1)]),
+                ProviderTrait::new(
+                    "TableStore",
+                    SDK,
+                    vec![ProviderMethod::new("query", 3), ProviderMethod::new("exec", 3)],
+                ),
+                ProviderTrait::new("Broadcast", SDK, vec![ProviderMethod::new("send", 3)]),
+            ],
+        }
+    }
+
+    /// Add traits to the catalog. A trait with the name of a known one
+    /// replaces it.
+    pub fn extend(&mut self, traits: impl IntoIterator<Item = ProviderTrait>) {
+        for provider in traits {
+            match self.traits.iter_mut().find(|t| t.name == provider.name) {
+                Some(existing) => *existing = provider,
+                None => self.traits.push(provider),
+            }
+        }
+    }
+
+    /// Every known trait, in catalog order.
+    pub fn traits(&self) -> &[ProviderTrait] {
+        &self.traits
+    }
+
+    /// Look up a trait by name.
+    pub fn get(&self, name: &str) -> Option<&ProviderTrait> {
+        self.traits.iter().find(|t| t.name == name)
+    }
+
+    /// Check if a name is a known provider trait.
+    pub fn is_provider_trait(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// The trait a path refers to: a bare trait name, or one qualified with
+    /// the trait's crate (`omnia_sdk::Config`).
+    pub fn trait_for_path(&self, segments: &[String]) -> Option<&ProviderTrait> {
+        let (name, prefix) = segments.split_last()?;
+        self.get(name).filter(|t| prefix.is_empty() || prefix.join("::") == t.crate_path)
+    }
+
+    /// Traits with a method called as `receiver.name(..)` with `arity`
+    /// arguments, where `is_async` tells whether the result is awaited and
+    /// `returns` names the type the result is used as (`String`, `Option`),
+    /// if known, and `in_scope` holds the provider bounds of the caller.
+    ///
+    /// Candidates are narrowed by name, then by arity, by the bounds in scope,
+    /// by whether the method is async and last by the type of its result,
+    /// skipping a step that would rule out every candidate. Only the
+    /// signature of the call overrides the bounds: code that awaits `get`
+    /// under `P: Config` calls `Config::get`, which is async too. Returns the
+    /// candidates that remain, in catalog order; more than one means the
+    /// call is ambiguous.
+    pub fn method_traits(
+        &self, name: &str, arity: usize, is_async: Option<bool>, returns: Option<&str>,
+        in_scope: &HashSet<String>,
+    ) -> Vec<&ProviderTrait> {
+        let mut candidates: Vec<(&ProviderTrait, &ProviderMethod)> = self
+            .traits
+            .iter()
+            .flat_map(|t| t.methods.iter().map(move |m| (t, m)))
+            .filter(|(_, m)| m.name == name && m.receiver != Receiver::None)
+            .collect();
+
+        narrow(&mut candidates, |_, m| m.arity == arity);
+        narrow(&mut candidates, |t, _| in_scope.contains(&t.name));
+        if let Some(is_async) = is_async {
+            narrow(&mut candidates, |_, m| m.is_async == is_async);
+        }
+        if let Some(returns) = returns {
+            narrow(&mut candidates, |_, m| m.returns_type(returns));
+        }
+
+        let mut traits: Vec<&ProviderTrait> = Vec::new();
+        for (provider, _) in candidates {
+            if !traits.iter().any(|t| t.name == provider.name) {
+                traits.push(provider);
+            }
+        }
+        traits
+    }
+}
+
+impl Default for ProviderCatalog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Keep the candidates that match, unless none do.
+fn narrow(
+    candidates: &mut Vec<(&ProviderTrait, &ProviderMethod)>,
+    keep: impl Fn(&ProviderTrait, &ProviderMethod) -> bool,
+) {
+    if candidates.iter().any(|(t, m)| keep(t, m)) {
+        candidates.retain(|(t, m)| keep(t, m));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(traits: Vec<&ProviderTrait>) -> Vec<&str> {
+        traits.into_iter().map(|t| t.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_defaults_cover_sdk_traits() {
+        let catalog = ProviderCatalog::new();
+        assert!(catalog.is_provider_trait("Config"));
+        assert!(catalog.is_provider_trait("Broadcast"));
+        assert!(!catalog.is_provider_trait("Debug"));
+
+        let path = |s: &str| s.split("::").map(String::from).collect::<Vec<_>>();
+        assert!(catalog.trait_for_path(&path("omnia_sdk::Config")).is_some());
+        assert!(catalog.trait_for_path(&path("Config")).is_some());
+        assert!(catalog.trait_for_path(&path("other::Config")).is_none());
+    }
+
+    #[test]
+    fn test_defaults_match_sdk_signatures() {
+        // omnia-sdk 0.28 `capabilities.rs`: every method takes `&self` and
+        // returns a future
+        let expected: &[(&str, &[(&str, usize)])] = &[
+            ("Config", &[("get", 1)]),
+            ("HttpRequest", &[("fetch", 1)]),
+            ("Publish", &[("send", 2)]),
+            ("StateStore", &[("get", 1), ("set", 3), ("delete", 1)]),
+            ("Identity", &[("access_token", 1)]),
+            ("TableStore", &[("query", 3), ("exec", 3)]),
+            ("Broadcast", &[("send", 3)]),
+        ];
+
+        let catalog = ProviderCatalog::new();
+        assert_eq!(catalog.traits().len(), expected.len());
+        for (provider, (name, methods)) in catalog.traits().iter().zip(expected) {
+            assert_eq!(provider.name, *name);
+            assert_eq!(provider.crate_path, "omnia_sdk");
+            let signatures: Vec<_> = provider
+                .methods
+                .iter()
+                .map(|m| (m.name.as_str(), m.arity, m.receiver, m.is_async))
+                .collect();
+            let sdk: Vec<_> =
+                methods.iter().map(|&(m, arity)| (m, arity, Receiver::Ref, true)).collect();
+            assert_eq!(signatures, sdk, "{}", name);
+        }
+
+        // Config::get and StateStore::get share their signature, so only the
+        // bounds in scope and the type of the result tell them apart
+        let none = HashSet::new();
+        assert_eq!(
+            names(catalog.method_traits("get", 1, Some(true), None, &none)),
+            vec!["Config", "StateStore"]
+        );
+        let returns = |name: &str| catalog.get(name).unwrap().methods[0].returns.as_deref();
+        assert_eq!(returns("Config"), Some("String"));
+        assert_eq!(returns("StateStore"), Some("Option<Vec<u8>>"));
+    }
+
+    fn bounds(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_method_traits_narrow_by_arity_and_await() {
+        let catalog = ProviderCatalog::new();
+        let none = HashSet::new();
+        assert_eq!(
+            names(catalog.method_traits("send", 2, Some(true), None, &none)),
+            vec!["Publish"]
+        );
+        assert_eq!(
+            names(catalog.method_traits("send", 3, Some(true), None, &none)),
+            vec!["Broadcast"]
+        );
+        assert_eq!(
+            names(catalog.method_traits("send", 1, Some(true), None, &none)),
+            vec!["Publish", "Broadcast"]
+        );
+        assert!(catalog.method_traits("unknown", 0, None, None, &none).is_empty());
+    }
+
+    #[test]
+    fn test_method_traits_narrow_by_bounds_in_scope() {
+        let catalog = ProviderCatalog::new();
+        let config = bounds(&["Config", "HttpRequest"]);
+        let state = bounds(&["StateStore"]);
+        let both = bounds(&["Config", "StateStore"]);
+        assert_eq!(
+            names(catalog.method_traits("get", 1, Some(true), None, &config)),
+            vec!["Config"]
+        );
+        assert_eq!(
+            names(catalog.method_traits("get", 1, Some(true), None, &state)),
+            vec!["StateStore"]
+        );
+        assert_eq!(
+            names(catalog.method_traits("get", 1, Some(true), None, &both)),
+            vec!["Config", "StateStore"]
+        );
+        // Arity still overrides the bounds, so a missing bound shows
+        assert_eq!(
+            names(catalog.method_traits("set", 3, Some(true), None, &config)),
+            vec!["StateStore"]
+        );
+    }
+
+    #[test]
+    fn test_method_traits_narrow_by_result_type() {
+        let catalog = ProviderCatalog::new();
+        let both = bounds(&["Config", "StateStore"]);
+        let get = |returns| names(catalog.method_traits("get", 1, Some(true), returns, &both));
+        assert_eq!(get(Some("String")), vec!["Config"]);
+        assert_eq!(get(Some("Option")), vec!["StateStore"]);
+        // A type neither resolves to rules out nothing
+        assert_eq!(get(Some("Vec")), vec!["Config", "StateStore"]);
+
+        // The bounds still come first
+        let state = bounds(&["StateStore"]);
+        assert_eq!(
+            names(catalog.method_traits("get", 1, Some(true), Some("String"), &state)),
+            vec!["StateStore"]
+        );
+    }
+
+    #[test]
+    fn test_extend_adds_and_replaces() {
+        let mut catalog = ProviderCatalog::new();
+        catalog.extend([
+            ProviderTrait::new("BlobStore", "my_sdk", vec![ProviderMethod::new("put", 2)]),
+            ProviderTrait::new("Config", "omnia_sdk", vec![ProviderMethod::new("get", 1).sync()]),
+        ]);
+        let none = HashSet::new();
+        assert_eq!(
+            names(catalog.method_traits("put", 2, Some(true), None, &none)),
+            vec!["BlobStore"]
+        );
+        assert_eq!(
+            names(catalog.method_traits("get", 1, Some(false), None, &none)),
+            vec!["Config"]
+        );
+        assert_eq!(catalog.traits().len(), 8);
+    }
+}
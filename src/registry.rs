@@ -0,0 +1,314 @@
+//! Registry of every rule the linter can report.
+//!
+//! Diagnostics come from four places: the regex [`RuleSet`], the forbidden
+//! patterns and crates in [`OmniaContext`], the semantic analyzer and the
+//! linter's own directive checks. The registry gives all of them one
+//! namespace of unique ids with their category and source.
+//!
+//! Some checks from different sources find the same problem (`std_fs` and
+//! `wasm_std_fs` both flag `std::fs::`). These are declared as alias groups:
+//! when several members of a group hit overlapping text on the same line,
+//! only the highest-ranked one is reported. Naming any member of a group in
+//! a directive, `[lints.omnia]` or `--disable` applies to the whole group.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::constraints::OmniaContext;
+use crate::diagnostics::{Diagnostic, LINT_RULES};
+use crate::rules::{RuleCategory, RuleSet};
+use crate::semantic::SEMANTIC_RULES;
+
+/// Checks that report the same finding, canonical id first.
+///
+/// The canonical id wins when members overlap; the rest are ranked in the
+/// order given.
+pub const ALIAS_GROUPS: &[&[&str]] = &[
+    &["wasm_std_fs", "std_fs"],
+    &["wasm_std_net", "std_net"],
+    &["wasm_std_thread", "std_thread", "thread_sleep"],
+    &["wasm_std_env", "std_env"],
+    &["wasm_std_process", "std_process"],
+    &["time_system_time_now", "std_time_system"],
+    &["stateless_static_mut", "global_state_static_mut"],
+    &["stateless_once_cell", "global_state_once_cell"],
+    &["stateless_lazy_static", "stateless_lazy_lock", "global_state_lazy"],
+    &["forbidden_crate_tokio", "forbidden_tokio"],
+    &["forbidden_crate_async-std", "forbidden_async_std"],
+    &["forbidden_crate_rayon", "forbidden_rayon"],
+    &["cache_missing_ttl", "statestore_no_ttl"],
+    &["handler_error_type", "error_anyhow_in_handler"],
+];
+
+/// Where a rule's diagnostics come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleSource {
+    /// A regex rule from [`RuleSet`].
+    Rule,
+    /// A forbidden code pattern.
+    ForbiddenPattern,
+    /// A forbidden crate (`forbidden_crate_<name>`).
+    ForbiddenCrate,
+    /// The `syn`-based semantic analyzer.
+    Semantic,
+    /// Checks of the linter's own directives and configuration.
+    Lint,
+}
+
+/// Metadata for one registered rule id.
+#[derive(Debug, Clone)]
+pub struct RegisteredRule {
+    /// Unique rule id.
+    pub id: String,
+
+    /// Category used for category-wide directives and config.
+    pub category: RuleCategory,
+
+    /// Where the rule's diagnostics come from.
+    pub source: RuleSource,
+
+    /// Index into [`ALIAS_GROUPS`], if the rule has aliases.
+    group: Option<usize>,
+
+    /// Position within its alias group; lower wins.
+    rank: usize,
+}
+
+/// Every rule id the linter can report, with alias groups.
+#[derive(Debug, Clone)]
+pub struct RuleRegistry {
+    rules: Vec<RegisteredRule>,
+    index: HashMap<String, usize>,
+}
+
+impl RuleRegistry {
+    /// Register the rules of every source.
+    ///
+    /// # Panics
+    ///
+    /// Panics if two sources declare the same id, or an alias group names
+    /// an id that no source declares.
+    pub fn new(rule_set: &RuleSet, context: &OmniaContext) -> Self {
+        let mut registry = Self {
+            rules: Vec::new(),
+            index: HashMap::new(),
+        };
+
+        for rule in &rule_set.rules {
+            registry.register(rule.id, rule.category, RuleSource::Rule);
+        }
+        for pattern in &context.forbidden_patterns {
+            registry.register(pattern.id, RuleCategory::Wasm, RuleSource::ForbiddenPattern);
+        }
+        let mut crates: Vec<&str> = context.forbidden_crates.iter().copied().collect();
+        crates.sort_unstable();
+        for name in crates {
+            registry.register(
+                &format!("forbidden_crate_{}", name),
+                RuleCategory::Wasm,
+                RuleSource::ForbiddenCrate,
+            );
+        }
+        for &(id, category) in SEMANTIC_RULES {
+            registry.register(id, category, RuleSource::Semantic);
+        }
+        for &id in LINT_RULES {
+            registry.register(id, RuleCategory::Lint, RuleSource::Lint);
+        }
+
+        for (group, members) in ALIAS_GROUPS.iter().enumerate() {
+            for (rank, id) in members.iter().enumerate() {
+                let idx = *registry
+                    .index
+                    .get(*id)
+                    .unwrap_or_else(|| panic!("alias group names unknown rule id `{}`", id));
+                registry.rules[idx].group = Some(group);
+                registry.rules[idx].rank = rank;
+            }
+        }
+
+        registry
+    }
+
+    fn register(&mut self, id: &str, category: RuleCategory, source: RuleSource) {
+        let idx = self.rules.len();
+        let previous = self.index.insert(id.to_string(), idx);
+        assert!(previous.is_none(), "duplicate rule id `{}`", id);
+        self.rules.push(RegisteredRule {
+            id: id.to_string(),
+            category,
+            source,
+            group: None,
+            rank: 0,
+        });
+    }
+
+    /// Look up a rule by id.
+    pub fn get(&self, id: &str) -> Option<&RegisteredRule> {
+        self.index.get(id).map(|&idx| &self.rules[idx])
+    }
+
+    /// Category of a rule, if the id is known.
+    pub fn category(&self, id: &str) -> Option<RuleCategory> {
+        self.get(id).map(|rule| rule.category)
+    }
+
+    /// Every registered rule, in registration order.
+    pub fn rules(&self) -> &[RegisteredRule] {
+        &self.rules
+    }
+
+    /// Canonical id of a rule's alias group, or the id itself.
+    pub fn canonical<'a>(&self, id: &'a str) -> &'a str {
+        match self.get(id).and_then(|rule| rule.group) {
+            Some(group) => ALIAS_GROUPS[group][0],
+            None => id,
+        }
+    }
+
+    /// Ids that report the same finding as `id`, canonical first.
+    ///
+    /// Contains just `id` when the rule has no aliases.
+    pub fn aliases<'a>(&self, id: &'a str) -> Vec<&'a str> {
+        match self.get(id).and_then(|rule| rule.group) {
+            Some(group) => ALIAS_GROUPS[group].to_vec(),
+            None => vec![id],
+        }
+    }
+
+    /// Add every alias of the named rules to a set of directive names.
+    pub fn expand_aliases(&self, names: &mut HashSet<String>) {
+        let aliases: Vec<String> =
+            names.iter().flat_map(|name| self.aliases(name)).map(String::from).collect();
+        names.extend(aliases);
+    }
+
+    /// Report each finding once.
+    ///
+    /// Diagnostics from the same alias group on the same line whose column
+    /// ranges overlap are one finding; the highest-ranked member is kept.
+    /// A rule that hits the same text through several patterns is
+    /// deduplicated the same way.
+    pub fn dedupe(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        let mut kept: Vec<Diagnostic> = Vec::with_capacity(diagnostics.len());
+
+        for diagnostic in diagnostics {
+            let canonical = self.canonical(&diagnostic.rule_id);
+            let duplicate = kept.iter_mut().find(|k| {
+                k.line == diagnostic.line
+                    && self.canonical(&k.rule_id) == canonical
+                    && overlaps(k, &diagnostic)
+            });
+            match duplicate {
+                Some(existing) if self.rank(&diagnostic.rule_id) < self.rank(&existing.rule_id) => {
+                    *existing = diagnostic;
+                }
+                Some(_) => {}
+                None => kept.push(diagnostic),
+            }
+        }
+
+        kept
+    }
+
+    fn rank(&self, id: &str) -> usize {
+        self.get(id).map_or(0, |rule| rule.rank)
+    }
+}
+
+/// Check if two diagnostics on the same line cover overlapping columns.
+fn overlaps(a: &Diagnostic, b: &Diagnostic) -> bool {
+    let a_end = a.end_column.max(a.column + 1);
+    let b_end = b.end_column.max(b.column + 1);
+    a.column < b_end && b.column < a_end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::RuleSeverity;
+
+    fn registry() -> RuleRegistry {
+        RuleRegistry::new(&RuleSet::new(), &OmniaContext::new())
+    }
+
+    fn diagnostic(rule_id: &str, line: usize, column: usize, end_column: usize) -> Diagnostic {
+        Diagnostic {
+            line,
+            column,
+            end_column,
+            severity: RuleSeverity::Error,
+            rule_id: rule_id.to_string(),
+            rule_name: String::new(),
+            category: RuleCategory::Wasm,
+            message: String::new(),
+            fix_template: None,
+            source_snippet: None,
+        }
+    }
+
+    #[test]
+    fn test_ids_are_unique_across_sources() {
+        let registry = registry();
+        assert_eq!(registry.get("std_fs").map(|r| r.source), Some(RuleSource::ForbiddenPattern));
+        assert_eq!(registry.get("wasm_std_fs").map(|r| r.source), Some(RuleSource::Rule));
+        assert_eq!(
+            registry.get("forbidden_crate_tokio").map(|r| r.source),
+            Some(RuleSource::ForbiddenCrate)
+        );
+        assert_eq!(registry.get("parse_error").map(|r| r.source), Some(RuleSource::Semantic));
+        assert_eq!(registry.category("println_debug"), Some(RuleCategory::Performance));
+        assert_eq!(registry.category("unknown_lint"), Some(RuleCategory::Lint));
+        assert!(registry.get("forbidden_crate_serde").is_none());
+    }
+
+    #[test]
+    fn test_alias_groups_do_not_overlap() {
+        let mut seen = HashSet::new();
+        for id in ALIAS_GROUPS.iter().flat_map(|group| group.iter()) {
+            assert!(seen.insert(id), "rule `{}` is in two alias groups", id);
+        }
+    }
+
+    #[test]
+    fn test_canonical_and_aliases() {
+        let registry = registry();
+        assert_eq!(registry.canonical("std_fs"), "wasm_std_fs");
+        assert_eq!(registry.canonical("wasm_std_fs"), "wasm_std_fs");
+        assert_eq!(registry.canonical("error_todo"), "error_todo");
+        assert_eq!(
+            registry.aliases("forbidden_tokio"),
+            vec!["forbidden_crate_tokio", "forbidden_tokio"]
+        );
+        assert_eq!(registry.aliases("error_todo"), vec!["error_todo"]);
+
+        let mut names: HashSet<String> = ["std_fs".to_string(), "error".to_string()].into();
+        registry.expand_aliases(&mut names);
+        assert!(names.contains("wasm_std_fs"));
+        assert_eq!(names.len(), 3);
+    }
+
+    #[test]
+    fn test_dedupe_keeps_highest_ranked_overlap() {
+        let registry = registry();
+        let kept = registry.dedupe(vec![
+            diagnostic("std_fs", 3, 4, 13),
+            diagnostic("wasm_std_fs", 3, 4, 13),
+            diagnostic("std_fs", 3, 0, 11),
+            diagnostic("std_fs", 7, 4, 13),
+            diagnostic("std_net", 3, 4, 13),
+        ]);
+        let ids: Vec<(&str, usize)> = kept.iter().map(|d| (d.rule_id.as_str(), d.line)).collect();
+        assert_eq!(ids, vec![("wasm_std_fs", 3), ("std_fs", 7), ("std_net", 3)]);
+    }
+
+    #[test]
+    fn test_dedupe_keeps_separate_findings() {
+        let registry = registry();
+        let kept = registry.dedupe(vec![
+            diagnostic("wasm_std_fs", 1, 4, 13),
+            diagnostic("std_fs", 1, 30, 39),
+            diagnostic("error_todo", 1, 4, 10),
+        ]);
+        assert_eq!(kept.len(), 3);
+    }
+}
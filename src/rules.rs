@@ -35,6 +35,23 @@ pub struct Rule {
 
     /// Reference to documentation.
     pub doc_reference: &'static str,
+
+    /// Text the pattern is matched against.
+    pub scope: RuleScope,
+}
+
+/// Text a rule's pattern is matched against.
+///
+/// Patterns that span several lines (a loop and its body, a chained call
+/// split by rustfmt) need a scope wider than a single line. Matches are
+/// mapped back to the line and column where they start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RuleScope {
+    /// Each line on its own.
+    #[default]
+    Line,
+    /// Each statement, including statements nested in blocks.
+    Statement,
 }
 
 /// Categories of rules.
@@ -66,6 +83,8 @@ pub enum RuleCategory {
     Time,
     /// Authentication and authorization rules.
     Auth,
+    /// Lint configuration, suppression directive and parse failure rules.
+    Lint,
 }
 
 impl RuleCategory {
@@ -84,6 +103,7 @@ impl RuleCategory {
         RuleCategory::Caching,
         RuleCategory::Time,
         RuleCategory::Auth,
+        RuleCategory::Lint,
     ];
 
     /// Convert a snake_case string key to a `RuleCategory`.
@@ -104,6 +124,7 @@ impl RuleCategory {
             "caching" => Some(RuleCategory::Caching),
             "time" => Some(RuleCategory::Time),
             "auth" => Some(RuleCategory::Auth),
+            "lint" => Some(RuleCategory::Lint),
             _ => None,
         }
     }
@@ -124,6 +145,7 @@ impl RuleCategory {
             RuleCategory::Caching => "caching",
             RuleCategory::Time => "time",
             RuleCategory::Auth => "auth",
+            RuleCategory::Lint => "lint",
         }
     }
 }
@@ -173,6 +195,16 @@ impl LintLevel {
         }
     }
 
+    /// Return the level as written in `Cargo.toml`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LintLevel::Allow => "allow",
+            LintLevel::Warn => "warn",
+            LintLevel::Deny => "deny",
+            LintLevel::Forbid => "forbid",
+        }
+    }
+
     /// Convert to the closest `RuleSeverity` for filtering.
     ///
     /// `Allow` maps to `None` (suppressed), the rest map to a severity.
@@ -227,7 +259,8 @@ impl Default for RuleSet {
 
 /// Construct a [`Rule`] with reduced boilerplate.
 ///
-/// `anti` defaults to `true`, `fix` and `doc` are optional.
+/// `anti` defaults to `true`, `scope` defaults to `Line`, `fix` and `doc`
+/// are optional.
 macro_rules! rule {
     (
         id: $id:expr,
@@ -236,6 +269,7 @@ macro_rules! rule {
         severity: $sev:ident,
         description: $desc:expr,
         pattern: $pat:expr
+        $(, scope: $scope:ident)?
         $(, anti: $anti:expr)?
         $(, fix: $fix:expr)?
         $(, doc: $doc:expr)?
@@ -251,8 +285,12 @@ macro_rules! rule {
             is_anti_pattern: rule!(@anti $($anti)?),
             fix_template: rule!(@opt $($fix)?),
             doc_reference: rule!(@doc $($doc)?),
+            scope: rule!(@scope $($scope)?),
         }
     };
+    // Default: match line by line
+    (@scope) => { RuleScope::Line };
+    (@scope $val:ident) => { RuleScope::$val };
     // Default: anti-pattern is true
     (@anti) => { true };
     (@anti $val:expr) => { $val };
@@ -268,16 +306,6 @@ macro_rules! rule {
 fn create_all_rules() -> Vec<Rule> {
     vec![
         // ==================== HANDLER RULES ====================
-        rule! {
-            id: "handler_async_handle",
-            name: "Handle Method is Async",
-            category: Handler,
-            severity: Error,
-            description: "The handle method must be async to support asynchronous provider operations.",
-            pattern: r"^\s*fn\s+handle\s*\(",
-            fix: "async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>>",
-            doc: "handler-trait.md",
-        },
         rule! {
             id: "handler_context_lifetime",
             name: "Context Lifetime Parameter",
@@ -588,7 +616,8 @@ fn create_all_rules() -> Vec<Rule> {
             category: Performance,
             severity: Hint,
             description: "Cloning inside loops may be inefficient. Consider borrowing.",
-            pattern: r"for\s+[^{]+\{[^}]*\.clone\s*\(",
+            pattern: r"\bfor\s+[^{]+\{[^}]*\.clone\s*\(",
+            scope: Statement,
             fix: "Use references or move ownership",
             doc: "ms-pragmatic-rust.md",
         },
@@ -618,7 +647,8 @@ fn create_all_rules() -> Vec<Rule> {
             category: Performance,
             severity: Hint,
             description: "format! allocates - consider preallocating strings outside loops.",
-            pattern: r"for\s+\w+\s+in[^{]*\{[^}]*format!\(",
+            pattern: r"\bfor\s+\w+\s+in[^{]*\{[^}]*format!\(",
+            scope: Statement,
             fix: "Preallocate String and use push_str",
             doc: "ms-pragmatic-rust.md#allocations",
         },
@@ -670,7 +700,8 @@ fn create_all_rules() -> Vec<Rule> {
             category: StrongTyping,
             severity: Hint,
             description: "Replace string literal matching with typed enums for compile-time safety.",
-            pattern: r#"match\s+\w+\.as_str\(\)\s*\{[^}]*"[^"]+"\s*=>"#,
+            pattern: r#"\bmatch\s+\w+\.as_str\(\)\s*\{[^}]*"[^"]+"\s*=>"#,
+            scope: Statement,
             fix: "Define an enum with #[derive(Deserialize)]",
             doc: "ms-pragmatic-rust.md#enums",
         },
@@ -723,7 +754,8 @@ fn create_all_rules() -> Vec<Rule> {
             category: Caching,
             severity: Warning,
             description: "StateStore::set should include a TTL to prevent unbounded cache growth.",
-            pattern: r"ctx\.provider\.set\([^)]*,\s*None\s*\)\.await",
+            pattern: r"ctx\.provider\s*\.set\s*\([^)]*,\s*None\s*,?\s*\)\s*\.await",
+            scope: Statement,
             fix: "ctx.provider.set(key, value, Some(Duration::from_secs(3600))).await?",
             doc: "cache-handler.md#ttl",
         },
@@ -800,7 +832,7 @@ mod tests {
     #[test]
     fn test_rule_set_creation() {
         let rule_set = RuleSet::new();
-        assert!(rule_set.rules.len() > 50, "Should have many rules defined");
+        assert!(rule_set.rules.len() >= 50, "Should have many rules defined");
     }
 
     #[test]
@@ -873,6 +905,13 @@ mod tests {
         assert_eq!(LintLevel::parse("invalid"), None);
     }
 
+    #[test]
+    fn test_lint_level_as_str_roundtrip() {
+        for level in [LintLevel::Allow, LintLevel::Warn, LintLevel::Deny, LintLevel::Forbid] {
+            assert_eq!(LintLevel::parse(level.as_str()), Some(level));
+        }
+    }
+
     #[test]
     fn test_lint_level_to_severity() {
         assert_eq!(LintLevel::Allow.to_severity(), None);
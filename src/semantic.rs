@@ -13,37 +13,156 @@
 //! generation) operates on the extracted data structures.
 
 use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::Path;
 
 use regex::Regex;
 use syn::spanned::Spanned;
 use syn::visit::Visit;
 use syn::{Expr, ExprAwait, ExprField, ExprMethodCall, ExprPath, Item};
 
+use crate::context::FileContext;
+use crate::crate_index::CrateIndex;
 use crate::diagnostics::Diagnostic;
+use crate::providers::ProviderCatalog;
 use crate::rules::{RuleCategory, RuleSeverity};
 
+/// Rule IDs emitted by the semantic analyzer, with their categories.
+pub const SEMANTIC_RULES: &[(&str, RuleCategory)] = &[
+    ("unused_provider_bound", RuleCategory::Provider),
+    ("missing_provider_bound", RuleCategory::Provider),
+    ("handler_missing_bounds", RuleCategory::Handler),
+    ("handler_generic_p", RuleCategory::Handler),
+    ("handler_output_type", RuleCategory::Handler),
+    ("handler_error_type", RuleCategory::Handler),
+    ("handler_input_vec_u8", RuleCategory::Handler),
+    ("handler_from_input_result", RuleCategory::Handler),
+    ("handler_async_handle", RuleCategory::Handler),
+    ("handler_handle_signature", RuleCategory::Handler),
+    ("handler_serde_deserialize", RuleCategory::Handler),
+    ("handler_serde_serialize", RuleCategory::Handler),
+    ("config_error_handling", RuleCategory::Error),
+    ("statestore_no_ttl", RuleCategory::Caching),
+    ("fetch_error_context", RuleCategory::Error),
+    ("unused_fn_provider_bound", RuleCategory::Provider),
+    ("missing_fn_provider_bound", RuleCategory::Provider),
+    ("ambiguous_provider_call", RuleCategory::Provider),
+    ("unused_struct_provider_bound", RuleCategory::Provider),
+    ("missing_struct_provider_bound", RuleCategory::Provider),
+    ("parse_error", RuleCategory::Lint),
+];
+
+/// Handler shape rules: `(id, name, severity, fix)`.
+const HANDLER_SHAPE_RULES: &[(&str, &str, RuleSeverity, &str)] = &[
+    (
+        "handler_generic_p",
+        "Handler Generic Parameter",
+        RuleSeverity::Error,
+        "impl<P: Config> Handler<P> for MyRequest",
+    ),
+    (
+        "handler_output_type",
+        "Handler Output Type Definition",
+        RuleSeverity::Error,
+        "type Output = MyResponse;",
+    ),
+    (
+        "handler_error_type",
+        "Handler Error Type",
+        RuleSeverity::Warning,
+        "type Error = omnia_sdk::Error;",
+    ),
+    ("handler_input_vec_u8", "Handler Input Type", RuleSeverity::Info, "type Input = Vec<u8>;"),
+    (
+        "handler_from_input_result",
+        "from_input Returns Result",
+        RuleSeverity::Error,
+        "fn from_input(input: Self::Input) -> Result<Self>",
+    ),
+    (
+        "handler_async_handle",
+        "Handle Method is Async",
+        RuleSeverity::Error,
+        "async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>>",
+    ),
+    (
+        "handler_handle_signature",
+        "Handle Method Signature",
+        RuleSeverity::Error,
+        "async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>>",
+    ),
+    (
+        "handler_serde_deserialize",
+        "Request Derives Deserialize",
+        RuleSeverity::Error,
+        "#[derive(Clone, Debug, Deserialize, Serialize)]",
+    ),
+    (
+        "handler_serde_serialize",
+        "Output Derives Serialize",
+        RuleSeverity::Error,
+        "#[derive(Clone, Debug, Deserialize, Serialize)]",
+    ),
+];
+
 /// Information about a helper function with provider bounds.
 #[derive(Debug, Clone)]
 pub struct FunctionInfo {
     /// Name of the function.
     pub name: String,
 
+    /// Qualified path of the function within the file, e.g. `load` or
+    /// `handlers::Repo::load`.
+    pub path: String,
+
     /// Line number where the function starts (0-indexed).
     pub line: usize,
 
-    /// Provider trait bounds declared on the function.
+    /// Provider trait bounds declared on the function, with composite
+    /// traits expanded to their provider supertraits.
     pub declared_bounds: HashSet<String>,
 
+    /// Provider traits named directly in the function's bounds.
+    pub direct_bounds: HashSet<String>,
+
     /// Provider traits directly used in the function body.
     pub direct_trait_usage: HashSet<String>,
 
-    /// Functions called from within this function.
+    /// Qualified paths of functions called from within this function.
     pub called_functions: HashSet<String>,
 
+    /// Provider method calls in the body that more than one trait could serve.
+    pub ambiguous_calls: Vec<AmbiguousCall>,
+
     /// Line range of the function.
     pub line_range: (usize, usize),
 }
 
+/// Functions of a file layered over those of its crate, keyed by path.
+///
+/// The file's own functions shadow the crate's copies, which were read from
+/// disk and may be out of date.
+#[derive(Debug, Clone, Copy)]
+struct FunctionMap<'a> {
+    /// Functions of the file being analyzed.
+    local: &'a HashMap<String, FunctionInfo>,
+    /// Functions of the rest of the crate, if the file belongs to one.
+    krate: Option<&'a HashMap<String, FunctionInfo>>,
+}
+
+impl<'a> FunctionMap<'a> {
+    fn get(self, path: &str) -> Option<&'a FunctionInfo> {
+        self.local.get(path).or_else(|| self.krate?.get(path))
+    }
+
+    /// Every function, with the file's copy of any it shares with the crate.
+    fn values(self) -> impl Iterator<Item = &'a FunctionInfo> {
+        let krate = self.krate.into_iter().flat_map(|krate| krate.iter());
+        let shadowed = krate.filter(move |(path, _)| !self.local.contains_key(*path));
+        self.local.values().chain(shadowed.map(|(_, info)| info))
+    }
+}
+
 /// Result of semantic analysis for a document.
 #[derive(Debug, Default)]
 pub struct SemanticAnalysisResult {
@@ -55,6 +174,9 @@ pub struct SemanticAnalysisResult {
 
     /// Provider trait usages detected.
     pub trait_usages: HashMap<String, Vec<TraitUsage>>,
+
+    /// Structs generic over a bounded provider found in the document.
+    pub provider_types: Vec<ProviderTypeInfo>,
 }
 
 /// Information about a Handler implementation.
@@ -66,20 +188,74 @@ pub struct HandlerInfo {
     /// Line number where the impl block starts (0-indexed).
     pub impl_line: usize,
 
-    /// Provider trait bounds declared on the impl.
+    /// Provider trait bounds declared on the impl, with composite traits
+    /// expanded to their provider supertraits.
     pub declared_bounds: HashSet<String>,
 
+    /// Provider traits named directly in the impl's bounds.
+    pub direct_bounds: HashSet<String>,
+
     /// Provider traits actually used in the handler body (including transitive usage via delegation).
     pub used_traits: HashSet<String>,
 
     /// Provider traits directly used in the handler body (before transitive resolution).
     pub direct_trait_usage: HashSet<String>,
 
-    /// Functions called from within this handler.
+    /// Qualified paths of functions called from within this handler.
     pub called_functions: HashSet<String>,
 
+    /// Provider method calls in the body that more than one trait could serve.
+    pub ambiguous_calls: Vec<AmbiguousCall>,
+
     /// Line range of the impl block.
     pub line_range: (usize, usize),
+
+    /// Generic provider parameter (`P` in `Handler<P>`), if the impl has one.
+    pub provider_param: Option<String>,
+
+    /// Problems with the impl's associated types and method signatures.
+    pub shape_issues: Vec<HandlerIssue>,
+}
+
+/// Information about a struct generic over a provider, such as
+/// `struct Catalog<'a, P: HttpRequest> { provider: &'a P }`.
+#[derive(Debug, Clone)]
+pub struct ProviderTypeInfo {
+    /// Qualified path of the struct.
+    pub path: String,
+
+    /// Line number of the struct declaration (0-indexed).
+    pub line: usize,
+
+    /// Provider trait bounds declared on the struct, with composite traits
+    /// expanded to their provider supertraits.
+    pub declared_bounds: HashSet<String>,
+
+    /// Provider traits named directly in the struct's bounds.
+    pub direct_bounds: HashSet<String>,
+
+    /// Provider traits used by the struct's methods, including transitive usage.
+    pub used_traits: HashSet<String>,
+
+    /// Qualified paths of the struct's methods that were analyzed.
+    pub methods: Vec<String>,
+
+    /// Provider method calls in the methods that more than one trait could serve.
+    pub ambiguous_calls: Vec<AmbiguousCall>,
+}
+
+/// A problem with a Handler impl's associated types or method signatures.
+#[derive(Debug, Clone)]
+pub struct HandlerIssue {
+    /// Rule that reports the problem.
+    pub rule_id: &'static str,
+
+    /// Byte range of the offending item, or of the impl header when an
+    /// item is missing.
+    pub span: Range<usize>,
+
+    /// Explanation of the problem.
+    pub message: String,
 }
 
 /// A detected usage of a provider trait.
@@ -98,18 +274,27 @@ pub struct TraitUsage {
     pub col_range: (usize, usize),
 }
 
+/// A provider method call that neither its arguments, the use of its result
+/// nor the bounds in scope attribute to a single trait.
+#[derive(Debug, Clone)]
+pub struct AmbiguousCall {
+    /// Method called on the provider.
+    pub method: String,
+
+    /// Traits that could serve the call, in catalog order.
+    pub candidates: Vec<String>,
+
+    /// Line number of the call (0-indexed).
+    pub line: usize,
+
+    /// Column range of the method name.
+    pub col_range: (usize, usize),
+}
+
 // ---------------------------------------------------------------------------
 // syn-based AST extraction
 // ---------------------------------------------------------------------------
 
-/// Check if a name is a known Omnia provider trait.
-fn is_provider_trait(name: &str) -> bool {
-    matches!(
-        name,
-        "Config" | "HttpRequest" | "Publisher" | "StateStore" | "Identity" | "TableStore"
-    )
-}
-
 /// Extract the last segment name from a `syn::Path` (e.g. `omnia_sdk::Config` -> `"Config"`).
 fn last_segment_name(path: &syn::Path) -> String {
     path.segments
@@ -118,39 +303,81 @@ fn last_segment_name(path: &syn::Path) -> String {
         .unwrap_or_default()
 }
 
-/// Extract provider trait bounds from `syn::Generics` (inline + where clause).
-fn extract_provider_bounds(generics: &syn::Generics) -> HashSet<String> {
-    let mut bounds = HashSet::new();
+/// Trait bounds on the type parameters of `syn::Generics` (inline + where clause).
+fn trait_bound_paths(generics: &syn::Generics) -> Vec<&syn::Path> {
+    let inline = generics.params.iter().flat_map(|param| match param {
+        syn::GenericParam::Type(type_param) => type_param.bounds.iter().collect(),
+        _ => Vec::new(),
+    });
+    let predicates = generics.where_clause.iter().flat_map(|where_clause| {
+        where_clause.predicates.iter().flat_map(|predicate| match predicate {
+            syn::WherePredicate::Type(pred) => pred.bounds.iter().collect(),
+            _ => Vec::new(),
+        })
+    });
+
+    inline
+        .chain(predicates)
+        .filter_map(|bound| match bound {
+            syn::TypeParamBound::Trait(trait_bound) => Some(&trait_bound.path),
+            _ => None,
+        })
+        .collect()
+}
 
-    for param in &generics.params {
-        if let syn::GenericParam::Type(type_param) = param {
-            for bound in &type_param.bounds {
-                if let syn::TypeParamBound::Trait(trait_bound) = bound {
-                    let name = last_segment_name(&trait_bound.path);
-                    if is_provider_trait(&name) {
-                        bounds.insert(name);
-                    }
-                }
-            }
-        }
-    }
+/// Trait bounds on one type parameter of `syn::Generics` (inline + where clause).
+fn param_bound_paths<'g>(
+    generics: &'g syn::Generics, param: &syn::TypeParam,
+) -> Vec<&'g syn::Path> {
+    let inline = generics.type_params().filter(|p| p.ident == param.ident).flat_map(|p| &p.bounds);
+    let predicates = generics
+        .where_clause
+        .iter()
+        .flat_map(|where_clause| &where_clause.predicates)
+        .filter_map(|predicate| match predicate {
+            syn::WherePredicate::Type(pred) => Some(pred),
+            _ => None,
+        })
+        .filter(|pred| match &pred.bounded_ty {
+            syn::Type::Path(ty) => ty.path.is_ident(&param.ident),
+            _ => false,
+        })
+        .flat_map(|pred| &pred.bounds);
+
+    inline
+        .chain(predicates)
+        .filter_map(|bound| match bound {
+            syn::TypeParamBound::Trait(trait_bound) => Some(&trait_bound.path),
+            _ => None,
+        })
+        .collect()
+}
 
-    if let Some(where_clause) = &generics.where_clause {
-        for predicate in &where_clause.predicates {
-            if let syn::WherePredicate::Type(pred) = predicate {
-                for bound in &pred.bounds {
-                    if let syn::TypeParamBound::Trait(trait_bound) = bound {
-                        let name = last_segment_name(&trait_bound.path);
-                        if is_provider_trait(&name) {
-                            bounds.insert(name);
-                        }
-                    }
-                }
-            }
+/// Position of the type parameter that `ty` is, or is a reference to.
+fn type_param_position(generics: &syn::Generics, ty: &syn::Type) -> Option<usize> {
+    match ty {
+        syn::Type::Reference(r) => type_param_position(generics, &r.elem),
+        syn::Type::Path(tp) if tp.qself.is_none() => {
+            let ident = tp.path.get_ident()?;
+            generics.type_params().position(|param| param.ident == *ident)
         }
+        _ => None,
     }
+}
+
+/// Extract the provider trait bounds named directly in `syn::Generics`
+/// (inline + where clause), without expanding composite traits.
+fn extract_provider_bounds(catalog: &ProviderCatalog, generics: &syn::Generics) -> HashSet<String> {
+    trait_bound_paths(generics)
+        .into_iter()
+        .filter_map(|path| catalog.trait_for_path(&path_segments(path)))
+        .map(|provider| provider.name.clone())
+        .collect()
+}
 
-    bounds
+/// Identifiers of a path's segments.
+fn path_segments(path: &syn::Path) -> Vec<String> {
+    path.segments.iter().map(|s| s.ident.to_string()).collect()
 }
 
 /// Extract a simple type name from `syn::Type` (handles `Path` types like `MyRequest`).
@@ -161,853 +388,3405 @@ fn type_name(ty: &syn::Type) -> String {
     }
 }
 
-/// Check if an expression refers to `provider` or `ctx.provider`.
-fn is_provider_receiver(expr: &Expr) -> bool {
-    match expr {
-        Expr::Path(ExprPath { path, .. }) => {
-            path.segments.len() == 1 && path.segments[0].ident == "provider"
-        }
-        Expr::Field(ExprField { base, member, .. }) => {
-            if let syn::Member::Named(ident) = member
-                && ident == "provider"
-                && let Expr::Path(ExprPath { path, .. }) = base.as_ref()
-            {
-                return path.segments.len() == 1 && path.segments[0].ident == "ctx";
-            }
-            false
+/// Join a module path and a name with `::`.
+fn qualify(module: &str, name: &str) -> String {
+    if module.is_empty() { name.to_string() } else { format!("{}::{}", module, name) }
+}
+
+/// Every item of a file, including those in nested inline modules, with the
+/// path of the module that declares it. `module` is the path of the file's
+/// own module (`""` for the crate root or a file analyzed on its own).
+fn module_items<'a>(file: &'a syn::File, module: &str) -> Vec<(String, &'a Item)> {
+    let mut items = Vec::new();
+    collect_module_items(&file.items, module, &mut items);
+    items
+}
+
+fn collect_module_items<'a>(items: &'a [Item], module: &str, out: &mut Vec<(String, &'a Item)>) {
+    for item in items {
+        out.push((module.to_string(), item));
+        if let Item::Mod(item_mod) = item
+            && let Some((_, content)) = &item_mod.content
+        {
+            collect_module_items(content, &qualify(module, &item_mod.ident.to_string()), out);
         }
-        // Handle &provider and &ctx.provider
-        Expr::Reference(r) => is_provider_receiver(&r.expr),
-        _ => false,
     }
 }
 
-/// Check if a method call expression is wrapped in `.await`.
-/// In syn, `expr.await` is `ExprAwait { base: expr }`.
-/// We check the parent context by looking at the call site instead.
-/// Since we can't see the parent from the child during Visit, we detect
-/// `.await` by checking if the method call is the base of an ExprAwait
-/// that we track separately.
+/// Check if an impl block implements a trait named `Handler`.
+fn is_handler_impl(impl_block: &syn::ItemImpl) -> bool {
+    impl_block
+        .trait_
+        .as_ref()
+        .is_some_and(|(_, trait_path, _)| last_segment_name(trait_path) == "Handler")
+}
+
+/// Check if an expression is `self`.
+fn is_self(expr: &Expr) -> bool {
+    matches!(expr, Expr::Path(ExprPath { path, .. }) if path.is_ident("self"))
+}
+
+/// Names declared and imported across a file or crate, for resolving calls
+/// to the qualified paths that [`extract_functions`] keys functions by.
 ///
-/// Instead, for StateStore vs Config disambiguation, we check whether the
-/// method call appears inside an `.await` by scanning the impl block's content
-/// textually for the specific pattern. This is a pragmatic hybrid approach.
-fn method_to_trait(method: &str) -> Option<&'static str> {
-    match method {
-        "fetch" => Some("HttpRequest"),
-        "send" => Some("Publisher"),
-        "set" | "delete" => Some("StateStore"),
-        "access_token" => Some("Identity"),
-        "query" | "exec" => Some("TableStore"),
-        _ => None,
-    }
+/// An index of one file can overlay the index of its crate: names are
+/// looked up in the file first, without copying the crate's index.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CallIndex<'p> {
+    /// Provider traits known to the analysis.
+    catalog: ProviderCatalog,
+    /// Index this one overlays, whose entries for the modules and types
+    /// declared here are out of date.
+    parent: Option<&'p CallIndex<'static>>,
+    /// Modules whose items were added to this index.
+    modules: HashSet<String>,
+    /// Qualified paths of every module, type, trait and function.
+    declared: HashSet<String>,
+    /// Qualified paths of every function and method.
+    functions: HashSet<String>,
+    /// Owners (qualified type or trait paths) of each method name.
+    methods: HashMap<String, Vec<String>>,
+    /// Qualified types of named struct fields, by struct path and field.
+    fields: HashMap<String, HashMap<String, String>>,
+    /// Names brought in by `use` in each module, with their qualified paths.
+    imports: HashMap<String, HashMap<String, String>>,
+    /// Modules glob-imported by each module.
+    globs: HashMap<String, Vec<String>>,
+    /// Supertraits of each trait and trait alias, as provider trait names
+    /// or qualified trait paths.
+    supertraits: HashMap<String, Vec<String>>,
+    /// Structs generic over a type, by path.
+    generic_structs: HashMap<String, GenericStruct>,
+    /// Trait bound targets of the (non-Handler) impls of each type.
+    impl_bounds: HashMap<String, Vec<String>>,
 }
 
-/// Visitor that walks expression trees to detect provider trait usage
-/// and function calls for transitive resolution.
-#[derive(Default)]
-struct TraitUsageVisitor {
-    used_traits: HashSet<String>,
-    called_functions: HashSet<String>,
-    trait_usages: Vec<TraitUsage>,
-    /// Track expressions that are the base of an `.await`.
-    /// We collect awaited method calls to disambiguate `provider.get` (Config vs StateStore).
-    awaited_spans: HashSet<(usize, usize)>,
+/// Type parameters and type-parameter fields of a generic struct, such as
+/// `struct Catalog<'a, P: HttpRequest> { provider: &'a P }`.
+#[derive(Debug, Clone, Default)]
+struct GenericStruct {
+    /// Bound targets of each type parameter, in declaration order.
+    params: Vec<Vec<String>>,
+    /// Fields typed with a type parameter or a reference to one, with the
+    /// position of the parameter.
+    fields: Vec<(String, usize)>,
 }
 
-impl TraitUsageVisitor {
-    /// First pass: collect all spans that are inside `.await` expressions.
-    fn collect_awaited_spans(&mut self, items: &[syn::ImplItem]) {
-        struct AwaitCollector {
-            spans: HashSet<(usize, usize)>,
-        }
-        impl<'ast> Visit<'ast> for AwaitCollector {
-            fn visit_expr_await(&mut self, node: &'ast ExprAwait) {
-                let start = node.base.span().start();
-                let end = node.base.span().end();
-                self.spans.insert((start.line, start.column));
-                let _ = end; // use start as the unique key
-                syn::visit::visit_expr_await(self, node);
-            }
+impl<'p> CallIndex<'p> {
+    /// An empty index that recognizes the traits of `catalog`.
+    pub(crate) fn new(catalog: &ProviderCatalog) -> Self {
+        Self {
+            catalog: catalog.clone(),
+            ..Self::default()
         }
-        let mut collector = AwaitCollector {
-            spans: HashSet::new(),
-        };
-        for item in items {
-            collector.visit_impl_item(item);
+    }
+
+    /// An empty index over `parent`, for the items of one of its files.
+    pub(crate) fn overlay(parent: &'p CallIndex<'static>) -> Self {
+        Self {
+            catalog: parent.catalog.clone(),
+            parent: Some(parent),
+            ..Self::default()
         }
-        self.awaited_spans = collector.spans;
     }
 
-    fn collect_awaited_spans_from_block(&mut self, block: &syn::Block) {
-        struct AwaitCollector {
-            spans: HashSet<(usize, usize)>,
+    /// Add the items of parsed files, each with the path of its module.
+    pub(crate) fn add_files(&mut self, files: &[(&syn::File, &str)]) {
+        let items: Vec<(String, &Item)> =
+            files.iter().flat_map(|&(file, module)| module_items(file, module)).collect();
+        self.modules.extend(files.iter().map(|&(_, module)| module.to_string()));
+        self.modules.extend(items.iter().map(|(module, _)| module.clone()));
+
+        // Imports first, so that impl and field types resolve through them
+        for (module, item) in &items {
+            match item {
+                Item::Use(item_use) => self.add_use(module, &mut Vec::new(), &item_use.tree),
+                Item::Mod(item) => self.declare(module, &item.ident),
+                Item::Struct(item) => self.declare(module, &item.ident),
+                Item::Enum(item) => self.declare(module, &item.ident),
+                Item::Trait(item) => self.declare(module, &item.ident),
+                Item::TraitAlias(item) => self.declare(module, &item.ident),
+                Item::Type(item) => self.declare(module, &item.ident),
+                Item::Fn(func) => {
+                    self.declare(module, &func.sig.ident);
+                    self.functions.insert(qualify(module, &func.sig.ident.to_string()));
+                }
+                _ => {}
+            }
         }
-        impl<'ast> Visit<'ast> for AwaitCollector {
-            fn visit_expr_await(&mut self, node: &'ast ExprAwait) {
-                let start = node.base.span().start();
-                self.spans.insert((start.line, start.column));
-                syn::visit::visit_expr_await(self, node);
+
+        for (module, item) in &items {
+            match item {
+                Item::Impl(impl_block) => {
+                    let owner = self.type_owner(module, &impl_block.self_ty);
+                    for impl_item in &impl_block.items {
+                        if let syn::ImplItem::Fn(method) = impl_item {
+                            self.add_method(&owner, &method.sig.ident);
+                        }
+                    }
+                    if !is_handler_impl(impl_block) {
+                        let targets: Vec<String> = trait_bound_paths(&impl_block.generics)
+                            .into_iter()
+                            .map(|path| self.bound_target(module, path))
+                            .collect();
+                        self.impl_bounds.entry(owner).or_default().extend(targets);
+                    }
+                }
+                Item::Trait(item_trait) => {
+                    let owner = qualify(module, &item_trait.ident.to_string());
+                    for trait_item in &item_trait.items {
+                        if let syn::TraitItem::Fn(method) = trait_item {
+                            self.add_method(&owner, &method.sig.ident);
+                        }
+                    }
+                    self.add_supertraits(module, &owner, &item_trait.supertraits);
+                }
+                Item::TraitAlias(alias) => {
+                    let owner = qualify(module, &alias.ident.to_string());
+                    self.add_supertraits(module, &owner, &alias.bounds);
+                }
+                Item::Struct(item_struct) => {
+                    let owner = qualify(module, &item_struct.ident.to_string());
+                    for field in &item_struct.fields {
+                        if let Some(ident) = &field.ident {
+                            let ty = self.type_owner(module, &field.ty);
+                            self.fields
+                                .entry(owner.clone())
+                                .or_default()
+                                .insert(ident.to_string(), ty);
+                        }
+                    }
+                    self.add_generic_struct(module, &owner, item_struct);
+                }
+                _ => {}
             }
         }
-        let mut collector = AwaitCollector {
-            spans: HashSet::new(),
-        };
-        collector.visit_block(block);
-        self.awaited_spans = collector.spans;
     }
 
-    fn is_awaited(&self, expr: &Expr) -> bool {
-        let start = expr.span().start();
-        self.awaited_spans.contains(&(start.line, start.column))
+    fn is_declared(&self, path: &str) -> bool {
+        self.declared.contains(path) || self.parent.is_some_and(|parent| parent.is_declared(path))
     }
-}
 
-impl<'ast> Visit<'ast> for TraitUsageVisitor {
-    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
-        let method = call.method.to_string();
-
-        if is_provider_receiver(&call.receiver) {
-            let line = call.method.span().start().line.saturating_sub(1);
-            let col_start = call.method.span().start().column;
-            let col_end = call.method.span().end().column;
+    fn is_function(&self, path: &str) -> bool {
+        self.functions.contains(path) || self.parent.is_some_and(|parent| parent.is_function(path))
+    }
 
-            if method == "get" {
-                // Disambiguate Config vs StateStore: .get(...).await -> StateStore
-                let trait_name =
-                    if self.is_awaited(&Expr::MethodCall(call.clone())) {
-                        "StateStore"
-                    } else {
-                        "Config"
-                    };
-                self.used_traits.insert(trait_name.to_string());
-                self.trait_usages.push(TraitUsage {
-                    trait_name: trait_name.to_string(),
-                    method: "get".to_string(),
-                    line,
-                    col_range: (col_start, col_end),
-                });
-            } else if let Some(trait_name) = method_to_trait(&method) {
-                self.used_traits.insert(trait_name.to_string());
-                self.trait_usages.push(TraitUsage {
-                    trait_name: trait_name.to_string(),
-                    method: method.clone(),
-                    line,
-                    col_range: (col_start, col_end),
-                });
+    /// Owners of the methods named `name`, here and in the parent.
+    fn method_owners(&self, name: &str) -> Vec<&str> {
+        let mut owners: Vec<&str> =
+            self.methods.get(name).into_iter().flatten().map(String::as_str).collect();
+        for owner in self.parent.map(|parent| parent.method_owners(name)).unwrap_or_default() {
+            if !owners.contains(&owner) {
+                owners.push(owner);
             }
         }
-
-        syn::visit::visit_expr_method_call(self, call);
+        owners
     }
 
-    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
-        if let Expr::Path(ExprPath { path, .. }) = call.func.as_ref() {
-            let segments: Vec<String> = path.segments.iter().map(|s| s.ident.to_string()).collect();
-
-            // UFCS calls: Config::get(provider, ...), HttpRequest::fetch(provider, ...) etc.
-            if segments.len() >= 2 {
-                let type_name = &segments[segments.len() - 2];
-                let method_name = &segments[segments.len() - 1];
-                if is_provider_trait(type_name) {
-                    let line = path.span().start().line.saturating_sub(1);
-                    let col_start = path.span().start().column;
-                    let col_end = path.span().end().column;
-                    self.used_traits.insert(type_name.clone());
-                    self.trait_usages.push(TraitUsage {
-                        trait_name: type_name.clone(),
-                        method: method_name.clone(),
-                        line,
-                        col_range: (col_start, col_end),
-                    });
-                }
-            }
-
-            // Single-segment function calls for transitive resolution
-            if segments.len() == 1 {
-                self.called_functions.insert(segments[0].clone());
-            }
+    /// Qualified type of a named field of the struct `ty`.
+    fn field_type(&self, ty: &str, field: &str) -> Option<&str> {
+        match self.fields.get(ty) {
+            Some(fields) => fields.get(field).map(String::as_str),
+            None => self.parent?.field_type(ty, field),
         }
-
-        syn::visit::visit_expr_call(self, call);
     }
-}
 
-/// Extract all Handler implementations from a parsed file.
-fn extract_handlers(
-    file: &syn::File, function_map: &HashMap<String, FunctionInfo>,
-) -> Vec<HandlerInfo> {
-    let mut handlers = Vec::new();
+    /// Qualified path of a name imported by `use` in `module`.
+    fn import(&self, module: &str, name: &str) -> Option<&str> {
+        if self.modules.contains(module) {
+            self.imports.get(module)?.get(name).map(String::as_str)
+        } else {
+            self.parent?.import(module, name)
+        }
+    }
 
-    for item in &file.items {
-        let Item::Impl(impl_block) = item else {
-            continue;
-        };
+    /// Modules glob-imported by `module`.
+    fn globs_of(&self, module: &str) -> &[String] {
+        if self.modules.contains(module) {
+            self.globs.get(module).map_or(&[], Vec::as_slice)
+        } else {
+            self.parent.map_or(&[], |parent| parent.globs_of(module))
+        }
+    }
 
-        // Check if this implements a trait ending in "Handler"
-        let Some((_, trait_path, _)) = &impl_block.trait_ else {
-            continue;
-        };
-        if last_segment_name(trait_path) != "Handler" {
-            continue;
+    fn supertraits_of(&self, target: &str) -> Option<&[String]> {
+        match self.supertraits.get(target) {
+            Some(supertraits) => Some(supertraits),
+            None => self.parent?.supertraits_of(target),
         }
+    }
 
-        let request_type = type_name(&impl_block.self_ty);
-        let declared_bounds = extract_provider_bounds(&impl_block.generics);
+    fn generic_struct(&self, ty: &str) -> Option<&GenericStruct> {
+        match self.generic_structs.get(ty) {
+            Some(generic) => Some(generic),
+            None => self.parent?.generic_struct(ty),
+        }
+    }
 
-        // Walk the impl body for trait usage and function calls
-        let mut visitor = TraitUsageVisitor::default();
-        visitor.collect_awaited_spans(&impl_block.items);
-        for impl_item in &impl_block.items {
-            visitor.visit_impl_item(impl_item);
+    /// Bound targets of the impls of `ty`, here and in the parent.
+    fn impl_bounds_of(&self, ty: &str) -> Vec<String> {
+        let mut bounds = self.impl_bounds.get(ty).cloned().unwrap_or_default();
+        if let Some(parent) = self.parent {
+            bounds.extend(parent.impl_bounds_of(ty));
         }
+        bounds
+    }
 
-        let used_traits = resolve_transitive_traits(
-            &visitor.used_traits,
-            &visitor.called_functions,
-            function_map,
-        );
+    fn add_generic_struct(&mut self, module: &str, owner: &str, item_struct: &syn::ItemStruct) {
+        let generics = &item_struct.generics;
+        if generics.type_params().next().is_none() {
+            return;
+        }
+        let params = generics
+            .type_params()
+            .map(|param| {
+                param_bound_paths(generics, param)
+                    .into_iter()
+                    .map(|path| self.bound_target(module, path))
+                    .collect()
+            })
+            .collect();
+        let fields = item_struct
+            .fields
+            .iter()
+            .filter_map(|field| {
+                let ident = field.ident.as_ref()?;
+                Some((ident.to_string(), type_param_position(generics, &field.ty)?))
+            })
+            .collect();
+        self.generic_structs.insert(owner.to_string(), GenericStruct { params, fields });
+    }
 
-        let impl_line = impl_block.impl_token.span.start().line.saturating_sub(1);
-        let end_line = impl_block
-            .brace_token
-            .span
-            .close()
-            .end()
-            .line
-            .saturating_sub(1);
+    /// Provider traits a generic struct requires of its provider, from the
+    /// bounds of the struct and of its impls. Empty for other types.
+    fn provider_type_bounds(&self, ty: &str) -> HashSet<String> {
+        let Some(generic) = self.generic_struct(ty) else {
+            return HashSet::new();
+        };
+        let impl_bounds = self.impl_bounds_of(ty);
+        self.expand_targets(generic.params.iter().flatten().cloned().chain(impl_bounds))
+    }
 
-        handlers.push(HandlerInfo {
-            request_type,
-            impl_line,
-            declared_bounds,
-            used_traits,
-            direct_trait_usage: visitor.used_traits,
-            called_functions: visitor.called_functions,
-            line_range: (impl_line, end_line),
-        });
+    /// Provider traits declared on a generic struct's own type parameters.
+    fn struct_bounds(&self, ty: &str) -> HashSet<String> {
+        self.generic_struct(ty)
+            .map(|generic| self.expand_targets(generic.params.iter().flatten().cloned()))
+            .unwrap_or_default()
     }
 
-    handlers
-}
+    fn declare(&mut self, module: &str, ident: &syn::Ident) {
+        self.declared.insert(qualify(module, &ident.to_string()));
+    }
 
-/// Check if a function signature has a parameter named `provider` or `ctx`.
-fn has_provider_param(sig: &syn::Signature) -> bool {
-    for arg in &sig.inputs {
-        if let syn::FnArg::Typed(pat_type) = arg
-            && let syn::Pat::Ident(pat_ident) = pat_type.pat.as_ref()
-        {
-            let name = pat_ident.ident.to_string();
-            if name == "provider" || name == "ctx" {
-                return true;
-            }
+    fn add_method(&mut self, owner: &str, ident: &syn::Ident) {
+        let name = ident.to_string();
+        self.functions.insert(qualify(owner, &name));
+        let owners = self.methods.entry(name).or_default();
+        if !owners.iter().any(|o| o == owner) {
+            owners.push(owner.to_string());
         }
     }
-    false
-}
-
-/// Extract all helper functions with provider bounds from a parsed file.
-fn extract_functions(file: &syn::File) -> HashMap<String, FunctionInfo> {
-    let mut map = HashMap::new();
 
-    for item in &file.items {
-        let Item::Fn(func) = item else { continue };
+    fn add_supertraits(
+        &mut self, module: &str, owner: &str,
+        bounds: &syn::punctuated::Punctuated<syn::TypeParamBound, syn::Token![+]>,
+    ) {
+        let targets = bounds
+            .iter()
+            .filter_map(|bound| match bound {
+                syn::TypeParamBound::Trait(trait_bound) => {
+                    Some(self.bound_target(module, &trait_bound.path))
+                }
+                _ => None,
+            })
+            .collect();
+        self.supertraits.insert(owner.to_string(), targets);
+    }
 
-        let bounds = extract_provider_bounds(&func.sig.generics);
-        if bounds.is_empty() && !has_provider_param(&func.sig) {
-            continue;
+    /// A provider trait name for a provider trait bound, else the qualified
+    /// path of the trait.
+    fn bound_target(&self, module: &str, path: &syn::Path) -> String {
+        let segments = path_segments(path);
+        match self.catalog.trait_for_path(&segments) {
+            Some(provider) => provider.name.clone(),
+            None => self.resolve(module, None, &segments),
         }
+    }
 
-        let mut visitor = TraitUsageVisitor::default();
-        visitor.collect_awaited_spans_from_block(&func.block);
-        visitor.visit_block(&func.block);
+    /// Provider trait bounds of `syn::Generics` used in `module`, with
+    /// composite traits such as `trait Provider: Config + HttpRequest {}`
+    /// and trait aliases expanded to their provider supertraits.
+    fn provider_bounds(&self, module: &str, generics: &syn::Generics) -> HashSet<String> {
+        self.expand_bounds(module, trait_bound_paths(generics))
+    }
 
-        let line = func.sig.fn_token.span.start().line.saturating_sub(1);
-        let end_line = func
-            .block
-            .brace_token
-            .span
-            .close()
-            .end()
-            .line
-            .saturating_sub(1);
+    /// Type parameters of `syn::Generics` bounded, inline or in the where
+    /// clause, by a provider trait or a composite of them.
+    fn provider_params(&self, module: &str, generics: &syn::Generics) -> HashSet<String> {
+        generics
+            .type_params()
+            .filter(|param| {
+                !self.expand_bounds(module, param_bound_paths(generics, param)).is_empty()
+            })
+            .map(|param| param.ident.to_string())
+            .collect()
+    }
 
-        map.insert(
-            func.sig.ident.to_string(),
-            FunctionInfo {
-                name: func.sig.ident.to_string(),
-                line,
-                declared_bounds: bounds,
-                direct_trait_usage: visitor.used_traits,
-                called_functions: visitor.called_functions,
-                line_range: (line, end_line),
-            },
-        );
+    /// Provider traits named by trait bound paths used in `module`, with
+    /// composite traits expanded.
+    fn expand_bounds<'a>(
+        &self, module: &str, paths: impl IntoIterator<Item = &'a syn::Path>,
+    ) -> HashSet<String> {
+        self.expand_targets(paths.into_iter().map(|path| self.bound_target(module, path)))
     }
 
-    map
-}
+    /// Provider traits among bound targets, as returned by `bound_target`,
+    /// with composite traits expanded.
+    fn expand_targets(&self, targets: impl IntoIterator<Item = String>) -> HashSet<String> {
+        let mut bounds = HashSet::new();
+        let mut seen = HashSet::new();
+        let mut pending: Vec<String> = targets.into_iter().collect();
+
+        while let Some(target) = pending.pop() {
+            if self.catalog.is_provider_trait(&target) {
+                bounds.insert(target);
+            } else if seen.insert(target.clone())
+                && let Some(supertraits) = self.supertraits_of(&target)
+            {
+                pending.extend(supertraits.iter().cloned());
+            }
+        }
 
-/// Collect all trait usages from the AST for reporting.
-fn collect_trait_usages_from_file(file: &syn::File) -> HashMap<String, Vec<TraitUsage>> {
-    let mut visitor = TraitUsageVisitor::default();
+        bounds
+    }
 
-    // Collect awaited spans from all functions and impl blocks
-    for item in &file.items {
-        match item {
-            Item::Fn(func) => {
-                visitor.collect_awaited_spans_from_block(&func.block);
+    fn add_use(&mut self, module: &str, prefix: &mut Vec<String>, tree: &syn::UseTree) {
+        match tree {
+            syn::UseTree::Path(path) => {
+                prefix.push(path.ident.to_string());
+                self.add_use(module, prefix, &path.tree);
+                prefix.pop();
+            }
+            syn::UseTree::Name(name) => self.add_import(module, prefix, &name.ident, &name.ident),
+            syn::UseTree::Rename(rename) => {
+                self.add_import(module, prefix, &rename.ident, &rename.rename);
+            }
+            syn::UseTree::Glob(_) => {
+                let target = self.resolve_base(module, prefix);
+                self.globs.entry(module.to_string()).or_default().push(target);
             }
-            Item::Impl(impl_block) => {
-                visitor.collect_awaited_spans(&impl_block.items);
+            syn::UseTree::Group(group) => {
+                for tree in &group.items {
+                    self.add_use(module, prefix, tree);
+                }
+            }
+        }
+    }
+
+    fn add_import(
+        &mut self, module: &str, prefix: &[String], ident: &syn::Ident, local: &syn::Ident,
+    ) {
+        // `use a::b::{self}` imports `b`
+        let (path, local) = if ident == "self" {
+            let Some(last) = prefix.last() else { return };
+            let local = if local == "self" { last.clone() } else { local.to_string() };
+            (prefix.to_vec(), local)
+        } else {
+            let mut path = prefix.to_vec();
+            path.push(ident.to_string());
+            (path, local.to_string())
+        };
+        let target = self.resolve_base(module, &path);
+        self.imports.entry(module.to_string()).or_default().insert(local, target);
+    }
+
+    /// Resolve `crate::`, `self::` and `super::` prefixes; any other path is
+    /// taken relative to `module`.
+    fn resolve_base(&self, module: &str, segments: &[String]) -> String {
+        let mut base: Vec<&str> =
+            if module.is_empty() { Vec::new() } else { module.split("::").collect() };
+        let mut rest = segments;
+        match rest.first().map(String::as_str) {
+            Some("crate") => {
+                base.clear();
+                rest = &rest[1..];
             }
+            Some("self") => rest = &rest[1..],
             _ => {}
         }
+        while rest.first().is_some_and(|segment| segment == "super") {
+            base.pop();
+            rest = &rest[1..];
+        }
+        base.extend(rest.iter().map(String::as_str));
+        base.join("::")
     }
 
-    // Now visit everything
-    for item in &file.items {
-        visitor.visit_item(item);
+    /// Qualified path of a path expression used in `module`, inside an impl
+    /// or trait whose `Self` is `self_ty`.
+    fn resolve(&self, module: &str, self_ty: Option<&str>, segments: &[String]) -> String {
+        let Some(first) = segments.first() else { return String::new() };
+        let rest = &segments[1..];
+        let join = |base: &str| rest.iter().fold(base.to_string(), |path, s| qualify(&path, s));
+
+        if first == "Self"
+            && let Some(self_ty) = self_ty
+        {
+            return join(self_ty);
+        }
+        if matches!(first.as_str(), "crate" | "self" | "super") {
+            return self.resolve_base(module, segments);
+        }
+        if let Some(target) = self.import(module, first) {
+            return join(target);
+        }
+        if !self.is_declared(&qualify(module, first)) {
+            let glob = self.globs_of(module).iter().find(|glob| {
+                let candidate = qualify(glob, first);
+                self.is_declared(&candidate) || self.is_function(&candidate)
+            });
+            if let Some(glob) = glob {
+                return join(&qualify(glob, first));
+            }
+        }
+        join(&qualify(module, first))
     }
 
-    let mut usages: HashMap<String, Vec<TraitUsage>> = HashMap::new();
-    for usage in visitor.trait_usages {
-        usages
-            .entry(usage.trait_name.clone())
-            .or_default()
-            .push(usage);
+    /// Qualified path of a type named in `module`, ignoring references and
+    /// generic arguments.
+    fn type_owner(&self, module: &str, ty: &syn::Type) -> String {
+        match ty {
+            syn::Type::Path(tp) => {
+                let segments: Vec<String> =
+                    tp.path.segments.iter().map(|s| s.ident.to_string()).collect();
+                self.resolve(module, None, &segments)
+            }
+            syn::Type::Reference(r) => self.type_owner(module, &r.elem),
+            _ => qualify(module, &type_name(ty)),
+        }
     }
-    usages
 }
 
-// ---------------------------------------------------------------------------
-// Analysis logic (unchanged from regex era)
-// ---------------------------------------------------------------------------
+/// Visitor that walks expression trees to detect provider trait usage
+/// and function calls for transitive resolution.
+struct TraitUsageVisitor<'i> {
+    /// Index for resolving calls and provider traits.
+    index: &'i CallIndex<'i>,
+    /// Path of the module the visited code is in.
+    module: String,
+    /// Qualified type or trait that `Self` refers to, inside an impl or trait.
+    self_ty: Option<String>,
+    /// Provider traits declared by the enclosing handler or function, used
+    /// to attribute methods that several traits share.
+    bounds: HashSet<String>,
+    /// Local names bound to the provider or to a request context.
+    bindings: Bindings,
+    used_traits: HashSet<String>,
+    called_functions: HashSet<String>,
+    trait_usages: Vec<TraitUsage>,
+    ambiguous_calls: Vec<AmbiguousCall>,
+    /// How the results of expressions are used, to tell provider methods
+    /// apart by `.await` and by the type of their result (`provider.get` on
+    /// StateStore vs Config).
+    result_uses: ResultUseCollector,
+}
 
-/// Resolve transitive trait requirements through function delegation.
-/// Traverses the call graph to find all traits that are transitively required.
-fn resolve_transitive_traits(
-    direct_usage: &HashSet<String>, called_functions: &HashSet<String>,
-    function_map: &HashMap<String, FunctionInfo>,
-) -> HashSet<String> {
-    let mut all_traits = direct_usage.clone();
-    let mut visited = HashSet::new();
-    let mut to_visit: Vec<String> = called_functions.iter().cloned().collect();
+/// What a local name holds, as far as provider receivers are concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Binding {
+    /// The provider, typed `P` or `&P` for a provider type parameter.
+    Provider,
+    /// A request context, typed `Context<'_, P>`, whose `provider` field is
+    /// the provider.
+    Context,
+}
 
-    while let Some(fn_name) = to_visit.pop() {
-        if visited.contains(&fn_name) {
-            continue;
-        }
-        visited.insert(fn_name.clone());
+/// Names in scope that hold the provider or a context, and the provider
+/// type parameters, for the intraprocedural tracking of provider receivers.
+#[derive(Debug, Clone, Default)]
+struct Bindings {
+    params: HashSet<String>,
+    names: HashMap<String, Binding>,
+    /// Fields of `self` that hold the provider, inside an impl.
+    fields: HashSet<String>,
+}
 
-        if let Some(fn_info) = function_map.get(&fn_name) {
-            all_traits.extend(fn_info.declared_bounds.iter().cloned());
-            all_traits.extend(fn_info.direct_trait_usage.iter().cloned());
+/// Collects the names a pattern binds.
+#[derive(Default)]
+struct PatNames(Vec<String>);
 
-            for called in &fn_info.called_functions {
-                if !visited.contains(called) {
-                    to_visit.push(called.clone());
-                }
-            }
-        }
+impl<'ast> Visit<'ast> for PatNames {
+    fn visit_pat_ident(&mut self, node: &'ast syn::PatIdent) {
+        self.0.push(node.ident.to_string());
+        syn::visit::visit_pat_ident(self, node);
     }
-
-    all_traits
 }
 
-// ---------------------------------------------------------------------------
-// SemanticAnalyzer (public API)
-// ---------------------------------------------------------------------------
+/// Position of an expression in the file, from its start to its end.
+type SpanKey = (usize, usize, usize, usize);
 
-/// Semantic analyzer for Omnia code.
+fn span_key(span: proc_macro2::Span) -> SpanKey {
+    let (start, end) = (span.start(), span.end());
+    (start.line, start.column, end.line, end.column)
+}
+
+/// Collects the expressions whose result is awaited, and those whose result
+/// is used as a value with `?` or a method call.
 ///
-/// Uses `syn` for AST-based extraction and regex for simple text-pattern
-/// checks (StateStore TTL, Config error handling, fetch context).
-pub struct SemanticAnalyzer;
+/// For awaited calls whose result is unwrapped (`call.await?`), it also
+/// records the name of the type the value is used as: the type annotation of
+/// the `let` it initializes, or `Option` when it is matched against `Some`
+/// or `None` or has an `Option` method called on it.
+#[derive(Default)]
+struct ResultUseCollector {
+    awaited: HashSet<SpanKey>,
+    used: HashSet<SpanKey>,
+    types: HashMap<SpanKey, String>,
+}
 
-impl SemanticAnalyzer {
-    /// Create a new semantic analyzer.
-    pub fn new() -> Self {
-        Self
+/// Methods of `Option` that `String`, `Vec` and the other provider result
+/// types do not have.
+const OPTION_METHODS: &[&str] = &[
+    "is_some",
+    "is_none",
+    "is_some_and",
+    "is_none_or",
+    "ok_or",
+    "ok_or_else",
+    "unwrap_or",
+    "unwrap_or_default",
+    "unwrap_or_else",
+    "as_deref",
+];
+
+impl ResultUseCollector {
+    /// Whether the result of the expression at `span` is awaited: `Some(true)`
+    /// for `.await`, `Some(false)` when used directly, `None` if unknown.
+    fn is_awaited(&self, span: proc_macro2::Span) -> Option<bool> {
+        let key = span_key(span);
+        if self.awaited.contains(&key) {
+            Some(true)
+        } else if self.used.contains(&key) {
+            Some(false)
+        } else {
+            None
+        }
     }
 
-    /// Perform semantic analysis on the given content.
-    pub fn analyze(&self, content: &str) -> SemanticAnalysisResult {
-        let file = match syn::parse_file(content) {
-            Ok(f) => f,
-            Err(_) => return SemanticAnalysisResult::default(),
-        };
+    /// The name of the type the awaited result of the expression at `span`
+    /// is used as, if known.
+    fn result_type(&self, span: proc_macro2::Span) -> Option<&str> {
+        self.types.get(&span_key(span)).map(String::as_str)
+    }
 
-        let mut result = SemanticAnalysisResult::default();
+    /// Record `expr`, if it unwraps an awaited call, as used as `ty`.
+    fn record_type(&mut self, expr: &Expr, ty: &str) {
+        if let Some(call) = awaited_call(expr)
+            && !ty.is_empty()
+        {
+            self.types.insert(span_key(call.span()), ty.to_string());
+        }
+    }
+}
 
-        let function_map = extract_functions(&file);
-        let handlers = extract_handlers(&file, &function_map);
+/// The call whose awaited result `expr` unwraps, as in `call.await?` or
+/// `call.await.unwrap()`.
+fn awaited_call(expr: &Expr) -> Option<&Expr> {
+    let unwrapped = match strip_parens(expr) {
+        Expr::Try(node) => &*node.expr,
+        Expr::MethodCall(node)
+            if matches!(node.method.to_string().as_str(), "unwrap" | "expect") =>
+        {
+            &*node.receiver
+        }
+        _ => return None,
+    };
+    match strip_parens(unwrapped) {
+        Expr::Await(node) => Some(&*node.base),
+        _ => None,
+    }
+}
 
-        for handler in &handlers {
-            result
-                .diagnostics
-                .extend(self.check_unused_bounds(handler, content));
-            result
-                .diagnostics
-                .extend(self.check_missing_bounds(handler, content));
+fn strip_parens(mut expr: &Expr) -> &Expr {
+    while let Expr::Paren(node) = expr {
+        expr = &node.expr;
+    }
+    expr
+}
 
-            // Handler with no provider bounds at all
-            if handler.declared_bounds.is_empty() {
-                let lines: Vec<&str> = content.lines().collect();
-                result.diagnostics.push(Diagnostic {
-                    line: handler.impl_line + 1,
-                    column: 0,
-                    end_column: lines.get(handler.impl_line).map_or(0, |l| l.len()),
-                    severity: RuleSeverity::Warning,
-                    rule_id: "handler_missing_bounds".to_string(),
-                    rule_name: "Handler Missing Provider Bounds".to_string(),
-                    category: RuleCategory::Handler,
-                    message: "Handler implementation should specify provider trait bounds."
-                        .to_string(),
-                    fix_template: Some(
-                        "impl<P: Config + HttpRequest> Handler<P> for ...".to_string(),
-                    ),
-                    source_snippet: lines.get(handler.impl_line).map(|s| s.to_string()),
-                });
-            }
+/// Whether a pattern matches an `Option`: `Some(..)` or `None`.
+fn is_option_pat(pat: &syn::Pat) -> bool {
+    let path = match pat {
+        syn::Pat::TupleStruct(node) => &node.path,
+        syn::Pat::Path(node) => &node.path,
+        syn::Pat::Ident(node) => return node.ident == "None",
+        _ => return false,
+    };
+    matches!(last_segment_name(path).as_str(), "Some" | "None")
+}
+
+impl<'ast> Visit<'ast> for ResultUseCollector {
+    fn visit_local(&mut self, node: &'ast syn::Local) {
+        if let (syn::Pat::Type(pat), Some(init)) = (&node.pat, &node.init) {
+            self.record_type(&init.expr, &type_name(&pat.ty));
         }
+        syn::visit::visit_local(self, node);
+    }
 
-        result.handlers = handlers;
-        result.trait_usages = collect_trait_usages_from_file(&file);
+    fn visit_expr_let(&mut self, node: &'ast syn::ExprLet) {
+        if is_option_pat(&node.pat) {
+            self.record_type(&node.expr, "Option");
+        }
+        syn::visit::visit_expr_let(self, node);
+    }
 
-        // Regex-based pattern checks (pragmatic hybrid -- these scan raw text)
-        result
-            .diagnostics
-            .extend(self.analyze_trait_patterns(content, &function_map));
+    fn visit_expr_match(&mut self, node: &'ast syn::ExprMatch) {
+        if node.arms.iter().any(|arm| is_option_pat(&arm.pat)) {
+            self.record_type(&node.expr, "Option");
+        }
+        syn::visit::visit_expr_match(self, node);
+    }
 
-        result
+    fn visit_expr_await(&mut self, node: &'ast ExprAwait) {
+        self.awaited.insert(span_key(node.base.span()));
+        syn::visit::visit_expr_await(self, node);
     }
 
-    /// Check for unused trait bounds in a handler.
-    fn check_unused_bounds(&self, handler: &HandlerInfo, content: &str) -> Vec<Diagnostic> {
-        let mut diagnostics = Vec::new();
-        let lines: Vec<&str> = content.lines().collect();
+    fn visit_expr_try(&mut self, node: &'ast syn::ExprTry) {
+        self.used.insert(span_key(node.expr.span()));
+        syn::visit::visit_expr_try(self, node);
+    }
 
-        for declared in &handler.declared_bounds {
-            if !handler.used_traits.contains(declared) {
-                diagnostics.push(Diagnostic {
-                    line: handler.impl_line + 1,
-                    column: 0,
-                    end_column: lines.get(handler.impl_line).map_or(0, |l| l.len()),
-                    severity: RuleSeverity::Warning,
-                    rule_id: "unused_provider_bound".to_string(),
-                    rule_name: "Unused Provider Trait Bound".to_string(),
-                    category: RuleCategory::Provider,
-                    message: format!(
-                        "Provider trait '{}' is declared but never used in handler '{}'.\n\nRemove unused trait from bounds to improve clarity.",
-                        declared, handler.request_type
-                    ),
-                    fix_template: Some(format!("Remove '{}' from provider bounds", declared)),
-                    source_snippet: lines.get(handler.impl_line).map(|s| s.to_string()),
-                });
-            }
+    fn visit_expr_method_call(&mut self, node: &'ast ExprMethodCall) {
+        self.used.insert(span_key(node.receiver.span()));
+        if OPTION_METHODS.contains(&node.method.to_string().as_str()) {
+            self.record_type(&node.receiver, "Option");
         }
+        syn::visit::visit_expr_method_call(self, node);
+    }
+}
 
-        diagnostics
+impl<'i> TraitUsageVisitor<'i> {
+    /// Visitor for code declared in `module`, within an impl or trait of
+    /// `self_ty` if given.
+    fn in_scope(index: &'i CallIndex<'i>, module: &str, self_ty: Option<&str>) -> Self {
+        Self {
+            index,
+            module: module.to_string(),
+            self_ty: self_ty.map(String::from),
+            bounds: HashSet::new(),
+            bindings: Bindings::default(),
+            used_traits: HashSet::new(),
+            called_functions: HashSet::new(),
+            trait_usages: Vec::new(),
+            ambiguous_calls: Vec::new(),
+            result_uses: ResultUseCollector::default(),
+        }
     }
 
-    /// Check for missing trait bounds in a handler.
-    fn check_missing_bounds(&self, handler: &HandlerInfo, content: &str) -> Vec<Diagnostic> {
-        let mut diagnostics = Vec::new();
-        let lines: Vec<&str> = content.lines().collect();
+    /// Attribute shared provider methods using the given bounds.
+    fn with_bounds(mut self, bounds: &HashSet<String>) -> Self {
+        self.bounds = bounds.clone();
+        self
+    }
 
-        for used in &handler.used_traits {
-            if !handler.declared_bounds.contains(used) {
-                diagnostics.push(Diagnostic {
-                    line: handler.impl_line + 1,
-                    column: 0,
-                    end_column: lines.get(handler.impl_line).map_or(0, |l| l.len()),
-                    severity: RuleSeverity::Error,
-                    rule_id: "missing_provider_bound".to_string(),
-                    rule_name: "Missing Provider Trait Bound".to_string(),
-                    category: RuleCategory::Provider,
-                    message: format!(
-                        "Handler '{}' uses trait '{}' but doesn't declare it in bounds.\n\nAdd '{}' to the provider bounds.",
-                        handler.request_type, used, used
-                    ),
-                    fix_template: Some(format!("Add '{}' to provider bounds", used)),
-                    source_snippet: lines.get(handler.impl_line).map(|s| s.to_string()),
-                });
+    /// Treat the provider-bounded type parameters of `generics` as provider
+    /// types.
+    fn add_provider_params(&mut self, generics: &syn::Generics) {
+        let params = self.index.provider_params(&self.module, generics);
+        self.bindings.params.extend(params);
+    }
+
+    /// Enter an impl: its provider type parameters become provider types,
+    /// and the fields of a generic struct typed with one of them hold the
+    /// provider.
+    fn enter_impl(&mut self, impl_block: &syn::ItemImpl) {
+        self.add_provider_params(&impl_block.generics);
+        let index = self.index;
+        let owner = index.type_owner(&self.module, &impl_block.self_ty);
+        let Some(generic) = index.generic_struct(&owner) else {
+            return;
+        };
+
+        // Type arguments of the self type, matched to the struct's parameters
+        let args: Vec<Option<String>> = match impl_block.self_ty.as_ref() {
+            syn::Type::Path(tp) => match tp.path.segments.last().map(|s| &s.arguments) {
+                Some(syn::PathArguments::AngleBracketed(args)) => args
+                    .args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        syn::GenericArgument::Type(ty) => Some(ty),
+                        _ => None,
+                    })
+                    .map(|ty| match ty {
+                        syn::Type::Path(ty) => ty.path.get_ident().map(|i| i.to_string()),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        };
+        for (field, position) in &generic.fields {
+            let bounded = generic
+                .params
+                .get(*position)
+                .is_some_and(|targets| !index.expand_targets(targets.iter().cloned()).is_empty());
+            let provider_arg = args
+                .get(*position)
+                .and_then(Option::as_ref)
+                .is_some_and(|arg| self.bindings.params.contains(arg));
+            if bounded || provider_arg {
+                self.bindings.fields.insert(field.clone());
             }
         }
+        self.bounds.extend(index.provider_type_bounds(&owner));
+    }
 
-        diagnostics
+    /// Record the construction of a type: building a provider-generic
+    /// struct requires the provider traits of its bounds.
+    fn record_construction(&mut self, ty: &str) {
+        if self.self_ty.as_deref() != Some(ty) {
+            self.used_traits.extend(self.index.provider_type_bounds(ty));
+        }
     }
 
-    /// Analyze trait usage patterns for additional diagnostics.
-    /// Uses regex for simple text-pattern checks that don't benefit from AST parsing.
-    fn analyze_trait_patterns(
-        &self, content: &str, function_map: &HashMap<String, FunctionInfo>,
-    ) -> Vec<Diagnostic> {
-        let mut diagnostics = Vec::new();
-        let lines: Vec<&str> = content.lines().collect();
+    /// Check if a function takes the provider or a context, by the types of
+    /// its parameters.
+    fn has_provider_param(&self, sig: &syn::Signature) -> bool {
+        sig.inputs.iter().any(|arg| {
+            matches!(arg, syn::FnArg::Typed(pat_type) if self.type_binding(&pat_type.ty).is_some())
+        })
+    }
 
-        let config_get_re =
-            Regex::new(r"provider\.get\s*\([^)]+\)").expect("valid regex: config_get");
-        for (line_idx, line) in lines.iter().enumerate() {
-            if config_get_re.is_match(line)
-                && !line.contains("unwrap_or")
-                && !line.contains("ok_or")
-                && !line.contains('?')
-                && !line.contains(".await?")
-            {
-                diagnostics.push(Diagnostic {
-                    line: line_idx + 1,
-                    column: 0,
-                    end_column: line.len(),
-                    severity: RuleSeverity::Hint,
-                    rule_id: "config_error_handling".to_string(),
-                    rule_name: "Config::get Error Handling".to_string(),
-                    category: RuleCategory::Error,
-                    message: "Config::get returns Result - consider using `?` or `.ok_or_else(|| bad_request!(\"missing config\"))?`".to_string(),
-                    fix_template: Some("Add `?` or proper error handling".to_string()),
-                    source_snippet: Some(line.to_string()),
-                });
+    /// Bind the parameters of a function, with its own type parameters.
+    fn bind_params(&mut self, sig: &syn::Signature) {
+        self.add_provider_params(&sig.generics);
+        for arg in &sig.inputs {
+            if let syn::FnArg::Typed(pat_type) = arg {
+                let binding = self.type_binding(&pat_type.ty);
+                self.bind_pat(&pat_type.pat, binding, None);
             }
         }
+    }
 
-        let set_no_ttl_re =
-            Regex::new(r"provider\.set\s*\([^,]+,[^,]+,\s*None\s*\)").expect("valid regex: set_no_ttl");
-        for (line_idx, line) in lines.iter().enumerate() {
-            if set_no_ttl_re.is_match(line) {
-                diagnostics.push(Diagnostic {
-                    line: line_idx + 1,
-                    column: 0,
-                    end_column: line.len(),
-                    severity: RuleSeverity::Warning,
-                    rule_id: "statestore_no_ttl".to_string(),
-                    rule_name: "StateStore::set Without TTL".to_string(),
-                    category: RuleCategory::Caching,
-                    message: "StateStore::set with None TTL - consider adding a TTL to prevent unbounded cache growth.\n\nExample: `Some(Duration::from_secs(3600))`".to_string(),
-                    fix_template: Some("Some(Duration::from_secs(3600))".to_string()),
-                    source_snippet: Some(line.to_string()),
+    /// What a value of type `ty` holds: a provider type parameter (or a
+    /// reference to one, or `impl Trait` of provider traits) or a context.
+    fn type_binding(&self, ty: &syn::Type) -> Option<Binding> {
+        match ty {
+            syn::Type::Reference(r) => self.type_binding(&r.elem),
+            syn::Type::Paren(p) => self.type_binding(&p.elem),
+            syn::Type::Path(tp) if tp.qself.is_none() => {
+                if tp
+                    .path
+                    .get_ident()
+                    .is_some_and(|ident| self.bindings.params.contains(&ident.to_string()))
+                {
+                    Some(Binding::Provider)
+                } else if last_segment_name(&tp.path) == "Context" {
+                    Some(Binding::Context)
+                } else {
+                    None
+                }
+            }
+            syn::Type::ImplTrait(it) => {
+                let paths = it.bounds.iter().filter_map(|bound| match bound {
+                    syn::TypeParamBound::Trait(trait_bound) => Some(&trait_bound.path),
+                    _ => None,
                 });
+                let provider = !self.index.expand_bounds(&self.module, paths).is_empty();
+                provider.then_some(Binding::Provider)
             }
+            _ => None,
         }
+    }
 
-        let fetch_re =
-            Regex::new(r"provider\.fetch\s*\([^)]+\)\.await\?").expect("valid regex: fetch_context");
-        for (line_idx, line) in lines.iter().enumerate() {
-            if fetch_re.is_match(line) && !line.contains(".context(") {
-                diagnostics.push(Diagnostic {
-                    line: line_idx + 1,
-                    column: 0,
-                    end_column: line.len(),
-                    severity: RuleSeverity::Hint,
-                    rule_id: "fetch_error_context".to_string(),
-                    rule_name: "HttpRequest::fetch Error Context".to_string(),
-                    category: RuleCategory::Error,
-                    message: "Consider adding `.context(\"fetching from API\")` for better error messages.".to_string(),
-                    fix_template: Some("Add `.context(\"description\")` before `?`".to_string()),
-                    source_snippet: Some(line.to_string()),
-                });
+    /// What an expression evaluates to, following bound names.
+    fn expr_binding(&self, expr: &Expr) -> Option<Binding> {
+        if self.is_provider_receiver(expr) {
+            return Some(Binding::Provider);
+        }
+        match expr {
+            Expr::Path(ExprPath { path, .. }) => {
+                let ident = path.get_ident()?.to_string();
+                self.bindings.names.get(&ident).copied()
             }
+            Expr::Reference(r) => self.expr_binding(&r.expr),
+            Expr::Paren(p) => self.expr_binding(&p.expr),
+            _ => None,
         }
+    }
 
-        self.check_function_trait_bounds(content, function_map, &mut diagnostics);
+    /// Bind the names of `pat` to what they hold, given the binding of the
+    /// whole value and, for `let`, the initializer. Names that hold neither
+    /// the provider nor a context are unbound, since they shadow.
+    fn bind_pat(&mut self, pat: &syn::Pat, binding: Option<Binding>, init: Option<&Expr>) {
+        match pat {
+            syn::Pat::Ident(pat_ident) if pat_ident.subpat.is_none() => {
+                let name = pat_ident.ident.to_string();
+                match binding {
+                    Some(binding) => self.bindings.names.insert(name, binding),
+                    None => self.bindings.names.remove(&name),
+                };
+            }
+            syn::Pat::Type(pat_type) => {
+                let binding = self.type_binding(&pat_type.ty).or(binding);
+                self.bind_pat(&pat_type.pat, binding, init);
+            }
+            syn::Pat::Reference(r) => self.bind_pat(&r.pat, binding, None),
+            syn::Pat::Paren(p) => self.bind_pat(&p.pat, binding, init),
+            syn::Pat::Tuple(tuple) => match init {
+                Some(Expr::Tuple(values)) if values.elems.len() == tuple.elems.len() => {
+                    for (pat, value) in tuple.elems.iter().zip(&values.elems) {
+                        let binding = self.expr_binding(value);
+                        self.bind_pat(pat, binding, Some(value));
+                    }
+                }
+                _ => self.unbind(pat),
+            },
+            syn::Pat::Struct(pat_struct) if binding == Some(Binding::Context) => {
+                for field in &pat_struct.fields {
+                    let is_provider =
+                        matches!(&field.member, syn::Member::Named(name) if name == "provider");
+                    let binding = is_provider.then_some(Binding::Provider);
+                    self.bind_pat(&field.pat, binding, None);
+                }
+            }
+            _ => self.unbind(pat),
+        }
+    }
 
-        diagnostics
+    /// Forget every name `pat` binds.
+    fn unbind(&mut self, pat: &syn::Pat) {
+        let mut names = PatNames::default();
+        names.visit_pat(pat);
+        for name in names.0 {
+            self.bindings.names.remove(&name);
+        }
     }
 
-    /// Check helper functions for proper trait bounds.
-    fn check_function_trait_bounds(
-        &self, content: &str, function_map: &HashMap<String, FunctionInfo>,
-        diagnostics: &mut Vec<Diagnostic>,
-    ) {
-        let lines: Vec<&str> = content.lines().collect();
+    /// Check if an expression is the provider: a name bound to it, the
+    /// `provider` field of a context, or a provider field of `self`.
+    fn is_provider_receiver(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Field(ExprField {
+                base,
+                member: syn::Member::Named(ident),
+                ..
+            }) if is_self(base) && self.bindings.fields.contains(&ident.to_string()) => true,
+            Expr::Path(ExprPath { path, .. }) => path.get_ident().is_some_and(|ident| {
+                self.bindings.names.get(&ident.to_string()) == Some(&Binding::Provider)
+            }),
+            Expr::Field(ExprField {
+                base,
+                member: syn::Member::Named(ident),
+                ..
+            }) if ident == "provider" => self.expr_binding(base) == Some(Binding::Context),
+            // Handle &provider, (provider) and *provider
+            Expr::Reference(r) => self.is_provider_receiver(&r.expr),
+            Expr::Paren(p) => self.is_provider_receiver(&p.expr),
+            Expr::Unary(syn::ExprUnary {
+                op: syn::UnOp::Deref(_),
+                expr,
+                ..
+            }) => self.is_provider_receiver(expr),
+            _ => false,
+        }
+    }
 
-        for fn_info in function_map.values() {
-            let line = lines.get(fn_info.line).copied().unwrap_or("");
+    /// Visit a function with its parameters bound, restoring the outer
+    /// bindings afterwards.
+    fn visit_fn_body(&mut self, sig: &syn::Signature, block: &syn::Block) {
+        let outer = self.bindings.clone();
+        self.bind_params(sig);
+        self.visit_block(block);
+        self.bindings = outer;
+    }
 
-            let used_traits = resolve_transitive_traits(
-                &fn_info.direct_trait_usage,
-                &fn_info.called_functions,
-                function_map,
-            );
+    /// Record a call to a function of the file.
+    fn record_call(&mut self, path: String) {
+        if self.index.is_function(&path) {
+            self.called_functions.insert(path);
+        }
+    }
 
-            for declared in &fn_info.declared_bounds {
-                if !used_traits.contains(declared) {
-                    diagnostics.push(Diagnostic {
-                        line: fn_info.line + 1,
-                        column: 0,
-                        end_column: line.len(),
-                        severity: RuleSeverity::Warning,
-                        rule_id: "unused_fn_provider_bound".to_string(),
-                        rule_name: "Unused Function Provider Trait Bound".to_string(),
-                        category: RuleCategory::Provider,
-                        message: format!(
-                            "Unused provider trait bound `{}` in function `{}`.\n\nConsider removing it to keep bounds minimal.",
-                            declared, fn_info.name
-                        ),
-                        fix_template: Some(format!("Remove '{}' from function bounds", declared)),
-                        source_snippet: Some(line.to_string()),
-                    });
-                }
+    /// Owner of a method called on `receiver`: the impl's own type for
+    /// `self`, the declared type for `self.field`, or else the only type or
+    /// trait in the file with a method of that name.
+    fn method_owner(&self, receiver: &Expr, method: &str) -> Option<String> {
+        let index = self.index;
+        let self_ty = self.self_ty.as_deref();
+        let known = match receiver {
+            Expr::Path(ExprPath { path, .. }) if path.is_ident("self") => self_ty.map(String::from),
+            Expr::Field(ExprField {
+                base,
+                member: syn::Member::Named(field),
+                ..
+            }) if matches!(base.as_ref(), Expr::Path(ExprPath { path, .. }) if path.is_ident("self")) => {
+                self_ty.and_then(|ty| index.field_type(ty, &field.to_string()).map(String::from))
             }
+            Expr::Reference(r) => return self.method_owner(&r.expr, method),
+            Expr::Paren(p) => return self.method_owner(&p.expr, method),
+            _ => None,
+        };
+        if let Some(owner) = known
+            && index.is_function(&qualify(&owner, method))
+        {
+            return Some(owner);
+        }
+        match index.method_owners(method).as_slice() {
+            [owner] => Some(owner.to_string()),
+            _ => None,
+        }
+    }
 
-            for used in &used_traits {
-                if !fn_info.declared_bounds.contains(used) && !fn_info.declared_bounds.is_empty() {
-                    diagnostics.push(Diagnostic {
-                        line: fn_info.line + 1,
-                        column: 0,
-                        end_column: line.len(),
-                        severity: RuleSeverity::Error,
-                        rule_id: "missing_fn_provider_bound".to_string(),
-                        rule_name: "Missing Function Provider Trait Bound".to_string(),
-                        category: RuleCategory::Provider,
-                        message: format!(
-                            "Missing provider trait bound `{}` in function `{}`.\n\nThe function uses `{}` methods but doesn't declare it in bounds.",
-                            used, fn_info.name, used
-                        ),
-                        fix_template: Some(format!("Add '{}' to function bounds", used)),
-                        source_snippet: Some(line.to_string()),
+    /// First pass: collect how the results of expressions are used.
+    fn collect_result_uses(&mut self, items: &[syn::ImplItem]) {
+        for item in items {
+            self.result_uses.visit_impl_item(item);
+        }
+    }
+
+    fn collect_result_uses_from_block(&mut self, block: &syn::Block) {
+        self.result_uses.visit_block(block);
+    }
+
+    fn collect_result_uses_from_file(&mut self, file: &syn::File) {
+        self.result_uses.visit_file(file);
+    }
+}
+
+impl<'ast> Visit<'ast> for TraitUsageVisitor<'_> {
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        let method = call.method.to_string();
+
+        if self.is_provider_receiver(&call.receiver) {
+            let line = call.method.span().start().line.saturating_sub(1);
+            let col_start = call.method.span().start().column;
+            let col_end = call.method.span().end().column;
+
+            // Methods shared by several traits (`get` on Config and StateStore)
+            // are told apart by argument count, the bounds in scope, `.await`
+            // and the type of the result; a call that is still shared is left
+            // unattributed
+            let awaited = self.result_uses.is_awaited(call.span());
+            let returns = self.result_uses.result_type(call.span());
+            let candidates = self.index.catalog.method_traits(
+                &method,
+                call.args.len(),
+                awaited,
+                returns,
+                &self.bounds,
+            );
+            match candidates.as_slice() {
+                [] => {}
+                [provider] => {
+                    self.used_traits.insert(provider.name.clone());
+                    self.trait_usages.push(TraitUsage {
+                        trait_name: provider.name.clone(),
+                        method: method.clone(),
+                        line,
+                        col_range: (col_start, col_end),
                     });
                 }
+                _ => self.ambiguous_calls.push(AmbiguousCall {
+                    method: method.clone(),
+                    candidates: candidates.iter().map(|t| t.name.clone()).collect(),
+                    line,
+                    col_range: (col_start, col_end),
+                }),
+            }
+        } else if let Some(owner) = self.method_owner(&call.receiver, &method) {
+            self.record_call(qualify(&owner, &method));
+        }
+
+        syn::visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        syn::visit::visit_local(self, local);
+        let init = local.init.as_ref().map(|init| init.expr.as_ref());
+        let binding = init.and_then(|expr| self.expr_binding(expr));
+        self.bind_pat(&local.pat, binding, init);
+    }
+
+    fn visit_block(&mut self, block: &'ast syn::Block) {
+        let outer = self.bindings.names.clone();
+        syn::visit::visit_block(self, block);
+        self.bindings.names = outer;
+    }
+
+    fn visit_expr_closure(&mut self, closure: &'ast syn::ExprClosure) {
+        let outer = self.bindings.names.clone();
+        for input in &closure.inputs {
+            self.bind_pat(input, None, None);
+        }
+        syn::visit::visit_expr_closure(self, closure);
+        self.bindings.names = outer;
+    }
+
+    fn visit_item_fn(&mut self, func: &'ast syn::ItemFn) {
+        self.visit_fn_body(&func.sig, &func.block);
+    }
+
+    fn visit_impl_item_fn(&mut self, method: &'ast syn::ImplItemFn) {
+        self.visit_fn_body(&method.sig, &method.block);
+    }
+
+    fn visit_trait_item_fn(&mut self, method: &'ast syn::TraitItemFn) {
+        if let Some(block) = &method.default {
+            self.visit_fn_body(&method.sig, block);
+        }
+    }
+
+    fn visit_item_impl(&mut self, impl_block: &'ast syn::ItemImpl) {
+        let outer = (self.bindings.clone(), self.bounds.clone(), self.self_ty.clone());
+        self.self_ty = Some(self.index.type_owner(&self.module, &impl_block.self_ty));
+        self.enter_impl(impl_block);
+        if is_handler_impl(impl_block) {
+            self.bindings
+                .params
+                .extend(impl_block.generics.type_params().map(|p| p.ident.to_string()));
+        }
+        syn::visit::visit_item_impl(self, impl_block);
+        (self.bindings, self.bounds, self.self_ty) = outer;
+    }
+
+    fn visit_expr_struct(&mut self, node: &'ast syn::ExprStruct) {
+        let segments = path_segments(&node.path);
+        let ty = self.index.resolve(&self.module, self.self_ty.as_deref(), &segments);
+        self.record_construction(&ty);
+        syn::visit::visit_expr_struct(self, node);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if let Expr::Path(ExprPath { path, .. }) = call.func.as_ref() {
+            let segments: Vec<String> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+
+            // UFCS calls: Config::get(provider, ...), HttpRequest::fetch(provider, ...) etc.
+            if let Some((method_name, trait_path)) = segments.split_last()
+                && let Some(provider) = self.index.catalog.trait_for_path(trait_path)
+            {
+                let line = path.span().start().line.saturating_sub(1);
+                let col_start = path.span().start().column;
+                let col_end = path.span().end().column;
+                self.used_traits.insert(provider.name.clone());
+                self.trait_usages.push(TraitUsage {
+                    trait_name: provider.name.clone(),
+                    method: method_name.clone(),
+                    line,
+                    col_range: (col_start, col_end),
+                });
+            }
+
+            // Calls to functions of the crate, for transitive resolution;
+            // `Catalog::new(..)` constructs a provider-generic struct
+            let resolved = self.index.resolve(&self.module, self.self_ty.as_deref(), &segments);
+            if let Some((owner, _)) = resolved.rsplit_once("::")
+                && self.index.is_function(&resolved)
+            {
+                self.record_construction(owner);
             }
+            self.record_call(resolved);
         }
+
+        syn::visit::visit_expr_call(self, call);
     }
 }
 
-impl Default for SemanticAnalyzer {
-    fn default() -> Self {
-        Self::new()
+/// Extract all Handler implementations from a parsed file, including those
+/// in nested inline modules.
+fn extract_handlers(
+    file: &syn::File, module: &str, index: &CallIndex, function_map: FunctionMap,
+) -> Vec<HandlerInfo> {
+    let mut handlers = Vec::new();
+
+    for (module, item) in module_items(file, module) {
+        let Item::Impl(impl_block) = item else {
+            continue;
+        };
+
+        // Check if this implements a trait ending in "Handler"
+        let Some((_, trait_path, _)) = &impl_block.trait_ else {
+            continue;
+        };
+        if last_segment_name(trait_path) != "Handler" {
+            continue;
+        }
+
+        let request_type = type_name(&impl_block.self_ty);
+        let declared_bounds = index.provider_bounds(&module, &impl_block.generics);
+        let direct_bounds = extract_provider_bounds(&index.catalog, &impl_block.generics);
+        let (provider_param, shape_issues) =
+            check_handler_shape(impl_block, trait_path, index, &module);
+
+        // Walk the impl body for trait usage and function calls
+        let self_ty = index.type_owner(&module, &impl_block.self_ty);
+        let mut visitor = TraitUsageVisitor::in_scope(index, &module, Some(&self_ty))
+            .with_bounds(&declared_bounds);
+        visitor.collect_result_uses(&impl_block.items);
+        visitor.enter_impl(impl_block);
+        visitor.bindings.params.extend(provider_param.clone());
+        for impl_item in &impl_block.items {
+            visitor.visit_impl_item(impl_item);
+        }
+
+        let used_traits = resolve_transitive_traits(
+            &visitor.used_traits,
+            &visitor.called_functions,
+            function_map,
+        );
+
+        let impl_line = impl_block.impl_token.span.start().line.saturating_sub(1);
+        let end_line = impl_block
+            .brace_token
+            .span
+            .close()
+            .end()
+            .line
+            .saturating_sub(1);
+
+        handlers.push(HandlerInfo {
+            request_type,
+            impl_line,
+            declared_bounds,
+            direct_bounds,
+            used_traits,
+            direct_trait_usage: visitor.used_traits,
+            called_functions: visitor.called_functions,
+            ambiguous_calls: visitor.ambiguous_calls,
+            line_range: (impl_line, end_line),
+            provider_param,
+            shape_issues,
+        });
+    }
+
+    handlers
+}
+
+/// Check a Handler impl's provider parameter, associated types and methods.
+///
+/// Returns the generic provider parameter, if any, and every problem found.
+fn check_handler_shape(
+    impl_block: &syn::ItemImpl, trait_path: &syn::Path, index: &CallIndex, module: &str,
+) -> (Option<String>, Vec<HandlerIssue>) {
+    let mut issues = Vec::new();
+    let header =
+        impl_block.impl_token.span.byte_range().start..impl_block.self_ty.span().byte_range().end;
+
+    // Handler<P> where P is a type parameter of the impl
+    let provider_arg = trait_path.segments.last().and_then(|segment| match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args.args.first(),
+        _ => None,
+    });
+    let provider_param = match provider_arg {
+        Some(syn::GenericArgument::Type(syn::Type::Path(ty)))
+            if ty.qself.is_none()
+                && impl_block
+                    .generics
+                    .type_params()
+                    .any(|param| ty.path.is_ident(&param.ident)) =>
+        {
+            ty.path.get_ident().map(|ident| ident.to_string())
+        }
+        _ => None,
+    };
+    if provider_param.is_none() {
+        let span = provider_arg.map_or(trait_path.span(), |arg| arg.span()).byte_range();
+        issues.push(HandlerIssue {
+            rule_id: "handler_generic_p",
+            span,
+            message: "Handler implementations must be generic over the provider type, so the runtime can inject its implementation.".to_string(),
+        });
+    }
+
+    let mut has_output = false;
+    for item in &impl_block.items {
+        match item {
+            syn::ImplItem::Type(ty) if ty.ident == "Output" => has_output = true,
+            syn::ImplItem::Type(ty)
+                if ty.ident == "Error" && !is_sdk_error(index, module, &ty.ty) =>
+            {
+                issues.push(HandlerIssue {
+                    rule_id: "handler_error_type",
+                    span: ty.span().byte_range(),
+                    message: "Handler Error type should be omnia_sdk::Error for proper HTTP status mapping.".to_string(),
+                });
+            }
+            syn::ImplItem::Type(ty) if ty.ident == "Input" && !is_byte_vec(&ty.ty) => {
+                issues.push(HandlerIssue {
+                    rule_id: "handler_input_vec_u8",
+                    span: ty.span().byte_range(),
+                    message: "Handler Input is typically Vec<u8>, the raw bytes of the request body, parsed by from_input.".to_string(),
+                });
+            }
+            syn::ImplItem::Fn(method) if method.sig.ident == "from_input" => {
+                let returns_result = match &method.sig.output {
+                    syn::ReturnType::Type(_, ty) => type_name(ty) == "Result",
+                    syn::ReturnType::Default => false,
+                };
+                if !returns_result {
+                    let span = match &method.sig.output {
+                        syn::ReturnType::Type(..) => method.sig.output.span(),
+                        syn::ReturnType::Default => method.sig.span(),
+                    };
+                    issues.push(HandlerIssue {
+                        rule_id: "handler_from_input_result",
+                        span: span.byte_range(),
+                        message: "from_input must return Result<Self> so invalid input is reported as an error.".to_string(),
+                    });
+                }
+            }
+            syn::ImplItem::Fn(method) if method.sig.ident == "handle" => {
+                let sig = &method.sig;
+                if sig.asyncness.is_none() {
+                    issues.push(HandlerIssue {
+                        rule_id: "handler_async_handle",
+                        span: sig.fn_token.span.byte_range().start..sig.ident.span().byte_range().end,
+                        message: "The handle method must be async to support asynchronous provider operations.".to_string(),
+                    });
+                }
+                if !is_handle_signature(sig, provider_param.as_deref()) {
+                    issues.push(HandlerIssue {
+                        rule_id: "handler_handle_signature",
+                        span: sig.paren_token.span.join().byte_range(),
+                        message: "The handle method must take `self` by value and the request context as `ctx: Context<'_, P>`.".to_string(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !has_output {
+        issues.push(HandlerIssue {
+            rule_id: "handler_output_type",
+            span: header,
+            message: "Handler implementations must declare the reply body type with `type Output = ...;`.".to_string(),
+        });
+    }
+
+    (provider_param, issues)
+}
+
+/// Check if a type named in `module` is `omnia_sdk::Error` or `omnia::Error`.
+///
+/// A bare name is resolved through the module's `use` imports, so
+/// `use anyhow::Error;` does not pass; without an import, `Error` passes
+/// only when it comes from a glob import of `omnia_sdk` or `omnia` and the
+/// module does not declare its own.
+fn is_sdk_error(index: &CallIndex, module: &str, ty: &syn::Type) -> bool {
+    let is_sdk = |path: &str| matches!(path, "omnia_sdk::Error" | "omnia::Error");
+    let syn::Type::Path(ty) = ty else { return false };
+    let segments: Vec<String> = ty.path.segments.iter().map(|s| s.ident.to_string()).collect();
+    // `use` paths are recorded relative to the module that imports them
+    let external = |path: &str| -> String {
+        let prefix = format!("{}::", module);
+        match path.strip_prefix(&prefix) {
+            Some(rest) if !module.is_empty() => rest.to_string(),
+            _ => path.to_string(),
+        }
+    };
+
+    match segments.as_slice() {
+        [name] => match index.import(module, name) {
+            Some(target) => is_sdk(&external(target)),
+            None => {
+                name == "Error"
+                    && !index.is_declared(&qualify(module, name))
+                    && index
+                        .globs_of(module)
+                        .iter()
+                        .any(|glob| matches!(external(glob).as_str(), "omnia_sdk" | "omnia"))
+            }
+        },
+        _ => is_sdk(&segments.join("::")),
+    }
+}
+
+/// Check for `Vec<u8>`, bare or as `std::vec::Vec<u8>`.
+fn is_byte_vec(ty: &syn::Type) -> bool {
+    let syn::Type::Path(ty) = ty else { return false };
+    let Some(last) = ty.path.segments.last() else { return false };
+    let syn::PathArguments::AngleBracketed(args) = &last.arguments else { return false };
+    last.ident == "Vec"
+        && matches!(
+            args.args.first(),
+            Some(syn::GenericArgument::Type(syn::Type::Path(arg))) if arg.path.is_ident("u8")
+        )
+        && args.args.len() == 1
+}
+
+/// Check for `(self, ctx: Context<'_, P>)`, with `P` the provider parameter.
+fn is_handle_signature(sig: &syn::Signature, provider_param: Option<&str>) -> bool {
+    let mut inputs = sig.inputs.iter();
+    let by_value_self = matches!(
+        inputs.next(),
+        Some(syn::FnArg::Receiver(receiver)) if receiver.reference.is_none()
+    );
+    let context = match inputs.next() {
+        Some(syn::FnArg::Typed(arg)) => match arg.ty.as_ref() {
+            syn::Type::Path(ty) => ty.path.segments.last().filter(|s| s.ident == "Context"),
+            _ => None,
+        },
+        _ => None,
+    };
+    let context_of_provider = context.is_some_and(|segment| match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args.args.iter().any(|arg| match arg {
+            syn::GenericArgument::Type(syn::Type::Path(ty)) => {
+                provider_param.is_none_or(|param| ty.path.is_ident(param))
+            }
+            _ => false,
+        }),
+        _ => false,
+    });
+
+    by_value_self && context_of_provider && inputs.next().is_none()
+}
+
+/// A type expression with its paths resolved to qualified paths.
+#[derive(Debug, Clone)]
+struct TypeRef {
+    path: String,
+    /// Type arguments, e.g. `Item` of `Vec<Item>`.
+    args: Vec<TypeRef>,
+}
+
+impl TypeRef {
+    /// Resolve a type named in `module`, if it is a path.
+    fn new(index: &CallIndex, module: &str, ty: &syn::Type) -> Option<Self> {
+        let syn::Type::Path(path) = ty else { return None };
+        let last = path.path.segments.last()?;
+        let segments: Vec<String> =
+            path.path.segments.iter().map(|s| s.ident.to_string()).collect();
+        let args = match &last.arguments {
+            syn::PathArguments::AngleBracketed(args) => args
+                .args
+                .iter()
+                .filter_map(|arg| match arg {
+                    syn::GenericArgument::Type(inner) => TypeRef::new(index, module, inner),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        Some(Self {
+            path: index.resolve(module, None, &segments),
+            args,
+        })
+    }
+}
+
+/// A struct or enum, with what serde needs to know.
+#[derive(Debug)]
+struct TypeDef {
+    /// Span of the type's name in its file, where problems are reported.
+    ident_span: Range<usize>,
+    /// Derived trait names, including those under `cfg_attr`.
+    derives: HashSet<String>,
+    /// Type named by `#[serde(from = "..")]` or `#[serde(try_from = "..")]`.
+    deserialize_via: Option<TypeRef>,
+    /// Type named by `#[serde(into = "..")]`.
+    serialize_via: Option<TypeRef>,
+}
+
+/// Struct, enum and type alias definitions of a file or crate, by
+/// qualified path.
+#[derive(Debug, Default)]
+pub(crate) struct TypeTable {
+    defs: HashMap<String, TypeDef>,
+    aliases: HashMap<String, TypeRef>,
+    /// Serde traits implemented by hand, by the qualified path of the type.
+    impls: HashMap<String, HashSet<String>>,
+}
+
+impl TypeTable {
+    /// Add the types of parsed files, each with the path of its module.
+    pub(crate) fn add_files(&mut self, index: &CallIndex, files: &[(&syn::File, &str)]) {
+        for &(file, module) in files {
+            for (module, item) in module_items(file, module) {
+                match item {
+                    Item::Struct(item) => self.add_def(index, &module, &item.ident, &item.attrs),
+                    Item::Enum(item) => self.add_def(index, &module, &item.ident, &item.attrs),
+                    Item::Type(item) => {
+                        if let Some(target) = TypeRef::new(index, &module, &item.ty) {
+                            self.aliases.insert(qualify(&module, &item.ident.to_string()), target);
+                        }
+                    }
+                    Item::Impl(impl_block) => {
+                        let Some((_, trait_path, _)) = &impl_block.trait_ else { continue };
+                        let name = last_segment_name(trait_path);
+                        if name == "Serialize" || name == "Deserialize" {
+                            let owner = index.type_owner(&module, &impl_block.self_ty);
+                            self.impls.entry(owner).or_default().insert(name);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn add_def(
+        &mut self, index: &CallIndex, module: &str, ident: &syn::Ident, attrs: &[syn::Attribute],
+    ) {
+        let mut def = TypeDef {
+            ident_span: ident.span().byte_range(),
+            derives: HashSet::new(),
+            deserialize_via: None,
+            serialize_via: None,
+        };
+        for attr in attrs {
+            collect_derives(&attr.meta, &mut def.derives);
+            if attr.path().is_ident("serde") {
+                for (key, ty) in serde_container_types(attr) {
+                    let via = TypeRef::new(index, module, &ty);
+                    match key.as_str() {
+                        "from" | "try_from" => def.deserialize_via = via,
+                        "into" => def.serialize_via = via,
+                        _ => {}
+                    }
+                }
+            }
+        }
+        self.defs.entry(qualify(module, &ident.to_string())).or_insert(def);
+    }
+}
+
+/// Types of a file layered over those of its crate, as [`FunctionMap`]
+/// layers functions.
+#[derive(Debug, Clone, Copy)]
+struct TypeTables<'a> {
+    local: &'a TypeTable,
+    krate: Option<&'a TypeTable>,
+}
+
+/// A definition a type refers to, with whether it is in the file itself.
+type FoundDef<'a> = (&'a str, &'a TypeDef, bool);
+
+impl<'a> TypeTables<'a> {
+    fn def(self, path: &str) -> Option<FoundDef<'a>> {
+        if let Some((path, def)) = self.local.defs.get_key_value(path) {
+            return Some((path, def, true));
+        }
+        let (path, def) = self.krate?.defs.get_key_value(path)?;
+        Some((path, def, false))
+    }
+
+    /// Check if the type at `path` derives or implements a serde trait.
+    fn implements(self, path: &str, def: &TypeDef, derive: &str) -> bool {
+        let implemented =
+            |table: &TypeTable| table.impls.get(path).is_some_and(|traits| traits.contains(derive));
+        def.derives.contains(derive)
+            || implemented(self.local)
+            || self.krate.is_some_and(implemented)
+    }
+
+    fn alias(self, path: &str) -> Option<&'a TypeRef> {
+        self.local.aliases.get(path).or_else(|| self.krate?.aliases.get(path))
+    }
+
+    /// Definitions a type refers to, following type aliases.
+    ///
+    /// A type that resolves to a struct or enum yields that definition;
+    /// otherwise (`Vec<Item>`, `Option<Item>`) its type arguments are searched.
+    fn resolve(self, ty: &TypeRef, found: &mut Vec<FoundDef<'a>>) {
+        self.resolve_depth(ty, found, 0);
+    }
+
+    fn resolve_depth(self, ty: &TypeRef, found: &mut Vec<FoundDef<'a>>, depth: usize) {
+        const MAX_ALIAS_DEPTH: usize = 8;
+
+        if let Some(def) = self.def(&ty.path) {
+            found.push(def);
+            return;
+        }
+        if let Some(target) = self.alias(&ty.path)
+            && depth < MAX_ALIAS_DEPTH
+        {
+            self.resolve_depth(target, found, depth + 1);
+            return;
+        }
+        for arg in &ty.args {
+            self.resolve_depth(arg, found, depth + 1);
+        }
+    }
+}
+
+/// Record trait names from `derive(...)`, including inside `cfg_attr(...)`.
+fn collect_derives(meta: &syn::Meta, derives: &mut HashSet<String>) {
+    let syn::Meta::List(list) = meta else { return };
+
+    if list.path.is_ident("derive") {
+        let paths = syn::parse::Parser::parse2(
+            syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated,
+            list.tokens.clone(),
+        );
+        for path in paths.into_iter().flatten() {
+            derives.insert(last_segment_name(&path));
+        }
+    } else if list.path.is_ident("cfg_attr")
+        && let Ok(nested) = syn::parse::Parser::parse2(
+            syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated,
+            list.tokens.clone(),
+        )
+    {
+        // The first entry is the predicate
+        for meta in nested.iter().skip(1) {
+            collect_derives(meta, derives);
+        }
+    }
+}
+
+/// `key = "Type"` entries of a `#[serde(...)]` container attribute.
+fn serde_container_types(attr: &syn::Attribute) -> Vec<(String, syn::Type)> {
+    let parser = syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated;
+    let Ok(nested) = attr.parse_args_with(parser) else {
+        return Vec::new();
+    };
+
+    nested
+        .iter()
+        .filter_map(|meta| {
+            let syn::Meta::NameValue(nv) = meta else { return None };
+            let syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(lit),
+                ..
+            }) = &nv.value
+            else {
+                return None;
+            };
+            let key = nv.path.get_ident()?.to_string();
+            Some((key, lit.parse::<syn::Type>().ok()?))
+        })
+        .collect()
+}
+
+/// Check that request types derive `Deserialize` and output types `Serialize`.
+///
+/// The request type of `impl Handler<P> for X` and the `type Output = Y` of
+/// each handler are resolved through imports to structs and enums of the
+/// file or of `krate`, through type aliases and generic wrappers such as
+/// `Vec<Y>`. A container attribute `#[serde(from = "Raw")]` or
+/// `#[serde(into = "Raw")]` moves the requirement to `Raw` as well. Types
+/// defined in this file are reported where they are defined, types defined
+/// elsewhere in the crate where the handler names them. Types outside the
+/// crate are not checked. A hand-written `impl Deserialize` or
+/// `impl Serialize` anywhere in the crate counts as the derive.
+fn check_serde_derives(
+    file: &syn::File, module: &str, index: &CallIndex, krate: Option<&TypeTable>,
+) -> Vec<HandlerIssue> {
+    let mut local = TypeTable::default();
+    local.add_files(index, &[(file, module)]);
+    let tables = TypeTables { local: &local, krate };
+    let mut issues = Vec::new();
+    let mut reported: HashSet<(&str, &str)> = HashSet::new();
+
+    for (module, item) in module_items(file, module) {
+        let Item::Impl(impl_block) = item else { continue };
+        if !is_handler_impl(impl_block) {
+            continue;
+        }
+
+        let output = impl_block.items.iter().find_map(|item| match item {
+            syn::ImplItem::Type(ty) if ty.ident == "Output" => Some(&ty.ty),
+            _ => None,
+        });
+        let checks = [
+            ("handler_serde_deserialize", "Deserialize", Some(&*impl_block.self_ty)),
+            ("handler_serde_serialize", "Serialize", output),
+        ];
+
+        for (rule_id, derive, ty) in checks {
+            let Some(ty) = ty else { continue };
+            let Some(type_ref) = TypeRef::new(index, &module, ty) else { continue };
+            let mut targets = Vec::new();
+            tables.resolve(&type_ref, &mut targets);
+
+            let mut seen = HashSet::new();
+            while let Some((name, def, is_local)) = targets.pop() {
+                if !seen.insert(name) {
+                    continue;
+                }
+                // Conversions named in serde container attributes must derive it too
+                let via = match derive {
+                    "Deserialize" => &def.deserialize_via,
+                    _ => &def.serialize_via,
+                };
+                if let Some(via) = via {
+                    tables.resolve(via, &mut targets);
+                }
+
+                if tables.implements(name, def, derive) || !reported.insert((rule_id, name)) {
+                    continue;
+                }
+                let role = if derive == "Deserialize" { "request" } else { "response" };
+                issues.push(HandlerIssue {
+                    rule_id,
+                    span: if is_local { def.ident_span.clone() } else { ty.span().byte_range() },
+                    message: format!(
+                        "`{}` is used as a handler {} type but does not derive {}.",
+                        name, role, derive
+                    ),
+                });
+            }
+        }
+    }
+
+    issues
+}
+
+/// Extract all helper functions with provider bounds from a parsed file.
+///
+/// Free functions, methods of inherent and trait impls (other than
+/// `Handler`) and trait default methods are collected from the file and its
+/// nested inline modules, keyed by qualified path.
+pub(crate) fn extract_functions(
+    file: &syn::File, module: &str, index: &CallIndex,
+) -> HashMap<String, FunctionInfo> {
+    let mut map = HashMap::new();
+    let mut add = |info: Option<FunctionInfo>| {
+        if let Some(info) = info {
+            map.insert(info.path.clone(), info);
+        }
+    };
+
+    for (module, item) in module_items(file, module) {
+        match item {
+            Item::Fn(func) => add(function_info(index, &module, None, &func.sig, &func.block)),
+            Item::Impl(impl_block) if !is_handler_impl(impl_block) => {
+                let owner = index.type_owner(&module, &impl_block.self_ty);
+                for impl_item in &impl_block.items {
+                    if let syn::ImplItem::Fn(method) = impl_item {
+                        add(function_info(
+                            index,
+                            &module,
+                            Some((&owner, Outer::Impl(impl_block))),
+                            &method.sig,
+                            &method.block,
+                        ));
+                    }
+                }
+            }
+            Item::Trait(item_trait) => {
+                let owner = qualify(&module, &item_trait.ident.to_string());
+                for trait_item in &item_trait.items {
+                    if let syn::TraitItem::Fn(method) = trait_item
+                        && let Some(block) = &method.default
+                    {
+                        add(function_info(
+                            index,
+                            &module,
+                            Some((&owner, Outer::Trait(item_trait))),
+                            &method.sig,
+                            block,
+                        ));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    map
+}
+
+/// The impl or trait a method is declared in.
+#[derive(Clone, Copy)]
+enum Outer<'a> {
+    Impl(&'a syn::ItemImpl),
+    Trait(&'a syn::ItemTrait),
+}
+
+/// Describe a function declared in `module`, as a method of the given
+/// owner path and impl or trait, if it takes a provider or is a method of a
+/// struct holding one.
+fn function_info(
+    index: &CallIndex, module: &str, owner: Option<(&str, Outer)>, sig: &syn::Signature,
+    block: &syn::Block,
+) -> Option<FunctionInfo> {
+    let bounds = index.provider_bounds(module, &sig.generics);
+    let mut visitor = TraitUsageVisitor::in_scope(index, module, owner.map(|(path, _)| path))
+        .with_bounds(&bounds);
+    match owner {
+        Some((_, Outer::Impl(impl_block))) => visitor.enter_impl(impl_block),
+        Some((_, Outer::Trait(item_trait))) => visitor.add_provider_params(&item_trait.generics),
+        None => {}
+    }
+    visitor.add_provider_params(&sig.generics);
+    let holds_provider = !visitor.bindings.fields.is_empty() && sig.receiver().is_some();
+    if bounds.is_empty() && !holds_provider && !visitor.has_provider_param(sig) {
+        return None;
+    }
+
+    visitor.collect_result_uses_from_block(block);
+    visitor.visit_fn_body(sig, block);
+
+    let line = sig.fn_token.span.start().line.saturating_sub(1);
+    let end_line = block.brace_token.span.close().end().line.saturating_sub(1);
+
+    Some(FunctionInfo {
+        name: sig.ident.to_string(),
+        path: qualify(owner.map_or(module, |(path, _)| path), &sig.ident.to_string()),
+        line,
+        declared_bounds: bounds,
+        direct_bounds: extract_provider_bounds(&index.catalog, &sig.generics),
+        direct_trait_usage: visitor.used_traits,
+        called_functions: visitor.called_functions,
+        ambiguous_calls: visitor.ambiguous_calls,
+        line_range: (line, end_line),
+    })
+}
+
+/// Extract the structs of a file whose type parameters have provider
+/// bounds, with the provider traits their methods use across `function_map`.
+fn extract_provider_types(
+    file: &syn::File, module: &str, index: &CallIndex, function_map: FunctionMap,
+) -> Vec<ProviderTypeInfo> {
+    let mut types = Vec::new();
+
+    for (module, item) in module_items(file, module) {
+        let Item::Struct(item_struct) = item else {
+            continue;
+        };
+        let path = qualify(&module, &item_struct.ident.to_string());
+        let declared_bounds = index.struct_bounds(&path);
+        if declared_bounds.is_empty() {
+            continue;
+        }
+
+        let mut methods = Vec::new();
+        let mut direct_usage = HashSet::new();
+        let mut called_functions = HashSet::new();
+        let mut ambiguous_calls = Vec::new();
+        for fn_info in function_map.values() {
+            if fn_info.path.rsplit_once("::").is_some_and(|(owner, _)| owner == path) {
+                methods.push(fn_info.path.clone());
+                direct_usage.extend(fn_info.direct_trait_usage.iter().cloned());
+                called_functions.extend(fn_info.called_functions.iter().cloned());
+                ambiguous_calls.extend(fn_info.ambiguous_calls.iter().cloned());
+            }
+        }
+        methods.sort_unstable();
+
+        types.push(ProviderTypeInfo {
+            line: item_struct.struct_token.span.start().line.saturating_sub(1),
+            declared_bounds,
+            direct_bounds: extract_provider_bounds(&index.catalog, &item_struct.generics),
+            used_traits: resolve_transitive_traits(&direct_usage, &called_functions, function_map),
+            methods,
+            ambiguous_calls,
+            path,
+        });
+    }
+
+    types
+}
+
+/// Collect all trait usages from the AST for reporting.
+fn collect_trait_usages_from_file(
+    file: &syn::File, index: &CallIndex,
+) -> HashMap<String, Vec<TraitUsage>> {
+    let mut visitor = TraitUsageVisitor::in_scope(index, "", None);
+    visitor.collect_result_uses_from_file(file);
+    visitor.visit_file(file);
+
+    let mut usages: HashMap<String, Vec<TraitUsage>> = HashMap::new();
+    for usage in visitor.trait_usages {
+        usages
+            .entry(usage.trait_name.clone())
+            .or_default()
+            .push(usage);
+    }
+    usages
+}
+
+// ---------------------------------------------------------------------------
+// Analysis logic (unchanged from regex era)
+// ---------------------------------------------------------------------------
+
+/// Whether `name` is one of the traits an ambiguous call could belong to.
+fn is_ambiguous_candidate(calls: &[AmbiguousCall], name: &str) -> bool {
+    calls.iter().any(|call| call.candidates.iter().any(|t| t == name))
+}
+
+/// Resolve transitive trait requirements through function delegation.
+/// Traverses the call graph to find all traits that are transitively required.
+fn resolve_transitive_traits(
+    direct_usage: &HashSet<String>, called_functions: &HashSet<String>, function_map: FunctionMap,
+) -> HashSet<String> {
+    let mut all_traits = direct_usage.clone();
+    let mut visited = HashSet::new();
+    let mut to_visit: Vec<String> = called_functions.iter().cloned().collect();
+
+    while let Some(fn_name) = to_visit.pop() {
+        if visited.contains(&fn_name) {
+            continue;
+        }
+        visited.insert(fn_name.clone());
+
+        if let Some(fn_info) = function_map.get(&fn_name) {
+            all_traits.extend(fn_info.declared_bounds.iter().cloned());
+            all_traits.extend(fn_info.direct_trait_usage.iter().cloned());
+
+            for called in &fn_info.called_functions {
+                if !visited.contains(called) {
+                    to_visit.push(called.clone());
+                }
+            }
+        }
+    }
+
+    all_traits
+}
+
+// ---------------------------------------------------------------------------
+// SemanticAnalyzer (public API)
+// ---------------------------------------------------------------------------
+
+/// Semantic analyzer for Omnia code.
+///
+/// Uses `syn` for AST-based extraction and regex for simple text-pattern
+/// checks (StateStore TTL, Config error handling, fetch context).
+pub struct SemanticAnalyzer {
+    /// Provider traits the bound analysis knows.
+    catalog: ProviderCatalog,
+}
+
+impl SemanticAnalyzer {
+    /// Create a new semantic analyzer for the Omnia SDK provider traits.
+    pub fn new() -> Self {
+        Self::with_catalog(ProviderCatalog::new())
+    }
+
+    /// Create a semantic analyzer for the provider traits of `catalog`.
+    pub fn with_catalog(catalog: ProviderCatalog) -> Self {
+        Self { catalog }
+    }
+
+    /// Provider traits the bound analysis knows.
+    pub fn catalog(&self) -> &ProviderCatalog {
+        &self.catalog
+    }
+
+    /// Perform semantic analysis on the given content.
+    pub fn analyze(&self, content: &str) -> SemanticAnalysisResult {
+        self.analyze_context(&FileContext::new(content))
+    }
+
+    /// Perform semantic analysis on an already parsed file.
+    ///
+    /// A file that does not parse is reported as `parse_error`; the checks
+    /// then run on whichever top-level items could be recovered.
+    pub fn analyze_context(&self, ctx: &FileContext) -> SemanticAnalysisResult {
+        self.analyze_module(ctx, None)
+    }
+
+    /// Perform semantic analysis on a file of a crate.
+    ///
+    /// Calls into other modules of the crate are resolved through `krate`,
+    /// so a handler's transitive trait usage covers helpers in other files.
+    /// Diagnostics are only reported for the handlers and helpers of this
+    /// file. A file outside the crate's module tree is analyzed on its own.
+    pub fn analyze_in_crate(
+        &self, ctx: &FileContext, krate: &CrateIndex, path: &Path,
+    ) -> SemanticAnalysisResult {
+        match krate.module_of(path) {
+            Some(module) => self.analyze_module(ctx, Some((krate, module))),
+            None => self.analyze_context(ctx),
+        }
+    }
+
+    fn analyze_module(
+        &self, ctx: &FileContext, krate: Option<(&CrateIndex, &str)>,
+    ) -> SemanticAnalysisResult {
+        let mut result = SemanticAnalysisResult::default();
+
+        if let Some(err) = &ctx.parse_error {
+            result.diagnostics.push(self.parse_error_diagnostic(ctx, err));
+        }
+        let Some(file) = &ctx.ast else {
+            return result;
+        };
+
+        // The file's own items take precedence over the crate's copy, which
+        // was read from disk and may be out of date
+        let module = krate.map_or("", |(_, module)| module);
+        let mut index = krate.map_or_else(
+            || CallIndex::new(&self.catalog),
+            |(krate, _)| CallIndex::overlay(krate.calls()),
+        );
+        index.add_files(&[(file, module)]);
+        let local_functions = extract_functions(file, module, &index);
+        let function_map = FunctionMap {
+            local: &local_functions,
+            krate: krate.map(|(krate, _)| krate.functions()),
+        };
+        let handlers = extract_handlers(file, module, &index, function_map);
+
+        for handler in &handlers {
+            result.diagnostics.extend(self.check_unused_bounds(handler, ctx));
+            result.diagnostics.extend(self.check_missing_bounds(handler, ctx));
+            result.diagnostics.extend(self.check_ambiguous_calls(&handler.ambiguous_calls, ctx));
+
+            result
+                .diagnostics
+                .extend(handler.shape_issues.iter().map(|issue| self.shape_diagnostic(ctx, issue)));
+
+            // Generic handler with no provider bounds at all
+            if handler.provider_param.is_some() && handler.declared_bounds.is_empty() {
+                let lines = &ctx.lines;
+                result.diagnostics.push(Diagnostic {
+                    line: handler.impl_line + 1,
+                    column: 0,
+                    end_column: lines.get(handler.impl_line).map_or(0, |l| l.len()),
+                    severity: RuleSeverity::Warning,
+                    rule_id: "handler_missing_bounds".to_string(),
+                    rule_name: "Handler Missing Provider Bounds".to_string(),
+                    category: RuleCategory::Handler,
+                    message: "Handler implementation should specify provider trait bounds."
+                        .to_string(),
+                    fix_template: Some(
+                        "impl<P: Config + HttpRequest> Handler<P> for ...".to_string(),
+                    ),
+                    source_snippet: lines.get(handler.impl_line).map(|s| s.to_string()),
+                });
+            }
+        }
+
+        result.diagnostics.extend(
+            check_serde_derives(file, module, &index, krate.map(|(krate, _)| krate.types()))
+                .iter()
+                .map(|issue| self.shape_diagnostic(ctx, issue)),
+        );
+
+        let provider_types = extract_provider_types(file, module, &index, function_map);
+        for provider_type in &provider_types {
+            result.diagnostics.extend(self.check_struct_bounds(provider_type, ctx));
+        }
+
+        result.handlers = handlers;
+        result.provider_types = provider_types;
+        result.trait_usages = collect_trait_usages_from_file(file, &index);
+
+        // Regex-based pattern checks (pragmatic hybrid -- these scan raw text)
+        result.diagnostics.extend(self.analyze_trait_patterns(ctx, function_map));
+
+        result
+    }
+
+    /// Report a Handler shape problem at the offending item.
+    fn shape_diagnostic(&self, ctx: &FileContext, issue: &HandlerIssue) -> Diagnostic {
+        let (rule_id, rule_name, severity, fix) = HANDLER_SHAPE_RULES
+            .iter()
+            .copied()
+            .find(|(id, ..)| *id == issue.rule_id)
+            .expect("handler shape issue has a rule");
+        let (line_idx, column) = ctx.line_col(issue.span.start);
+        let (end_line, end_col) = ctx.line_col(issue.span.end);
+        let line = ctx.line(line_idx);
+
+        Diagnostic {
+            line: line_idx + 1,
+            column,
+            end_column: if end_line == line_idx { end_col } else { line.len().max(column) },
+            severity,
+            rule_id: rule_id.to_string(),
+            rule_name: rule_name.to_string(),
+            category: RuleCategory::Handler,
+            message: issue.message.clone(),
+            fix_template: Some(fix.to_string()),
+            source_snippet: Some(line.to_string()),
+        }
+    }
+
+    /// Report a syntax error at its span.
+    fn parse_error_diagnostic(&self, ctx: &FileContext, err: &syn::Error) -> Diagnostic {
+        let range = err.span().byte_range();
+        let (line_idx, column) = ctx.line_col(range.start.min(ctx.source.len()));
+        let line = ctx.line(line_idx);
+        let end_column = if ctx.line_col(range.end).0 == line_idx {
+            ctx.line_col(range.end).1.max(column)
+        } else {
+            line.len().max(column)
+        };
+
+        Diagnostic {
+            line: line_idx + 1,
+            column,
+            end_column,
+            severity: RuleSeverity::Error,
+            rule_id: "parse_error".to_string(),
+            rule_name: "Parse Error".to_string(),
+            category: RuleCategory::Lint,
+            message: format!(
+                "File does not parse: {}.\n\nSyntax-tree checks only ran on the top-level items that parse.",
+                err
+            ),
+            fix_template: None,
+            source_snippet: Some(line.to_string()),
+        }
+    }
+
+    /// Check for unused trait bounds in a handler.
+    ///
+    /// Only traits named in the bounds are reported; those that come with a
+    /// composite trait cannot be removed on their own.
+    fn check_unused_bounds(&self, handler: &HandlerInfo, ctx: &FileContext) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let lines = &ctx.lines;
+
+        for declared in &handler.direct_bounds {
+            if !handler.used_traits.contains(declared)
+                && !is_ambiguous_candidate(&handler.ambiguous_calls, declared)
+            {
+                diagnostics.push(Diagnostic {
+                    line: handler.impl_line + 1,
+                    column: 0,
+                    end_column: lines.get(handler.impl_line).map_or(0, |l| l.len()),
+                    severity: RuleSeverity::Warning,
+                    rule_id: "unused_provider_bound".to_string(),
+                    rule_name: "Unused Provider Trait Bound".to_string(),
+                    category: RuleCategory::Provider,
+                    message: format!(
+                        "Provider trait '{}' is declared but never used in handler '{}'.\n\nRemove unused trait from bounds to improve clarity.",
+                        declared, handler.request_type
+                    ),
+                    fix_template: Some(format!("Remove '{}' from provider bounds", declared)),
+                    source_snippet: lines.get(handler.impl_line).map(|s| s.to_string()),
+                });
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Check for missing trait bounds in a handler.
+    fn check_missing_bounds(&self, handler: &HandlerInfo, ctx: &FileContext) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let lines = &ctx.lines;
+
+        for used in &handler.used_traits {
+            if !handler.declared_bounds.contains(used) {
+                diagnostics.push(Diagnostic {
+                    line: handler.impl_line + 1,
+                    column: 0,
+                    end_column: lines.get(handler.impl_line).map_or(0, |l| l.len()),
+                    severity: RuleSeverity::Error,
+                    rule_id: "missing_provider_bound".to_string(),
+                    rule_name: "Missing Provider Trait Bound".to_string(),
+                    category: RuleCategory::Provider,
+                    message: format!(
+                        "Handler '{}' uses trait '{}' but doesn't declare it in bounds.\n\nAdd '{}' to the provider bounds.",
+                        handler.request_type, used, used
+                    ),
+                    fix_template: Some(format!("Add '{}' to provider bounds", used)),
+                    source_snippet: lines.get(handler.impl_line).map(|s| s.to_string()),
+                });
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Check a provider-generic struct's bounds against the provider traits
+    /// its methods use. Structs whose methods were not found are skipped.
+    fn check_struct_bounds(&self, info: &ProviderTypeInfo, ctx: &FileContext) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        if info.methods.is_empty() {
+            return diagnostics;
+        }
+        let line = ctx.lines.get(info.line).copied().unwrap_or("");
+
+        for declared in &info.direct_bounds {
+            if !info.used_traits.contains(declared)
+                && !is_ambiguous_candidate(&info.ambiguous_calls, declared)
+            {
+                diagnostics.push(Diagnostic {
+                    line: info.line + 1,
+                    column: 0,
+                    end_column: line.len(),
+                    severity: RuleSeverity::Warning,
+                    rule_id: "unused_struct_provider_bound".to_string(),
+                    rule_name: "Unused Struct Provider Trait Bound".to_string(),
+                    category: RuleCategory::Provider,
+                    message: format!(
+                        "Provider trait '{}' is declared on struct '{}' but none of its methods use it.\n\nHandlers that construct the struct must satisfy every bound, so keep them minimal.",
+                        declared, info.path
+                    ),
+                    fix_template: Some(format!("Remove '{}' from struct bounds", declared)),
+                    source_snippet: Some(line.to_string()),
+                });
+            }
+        }
+
+        for used in &info.used_traits {
+            if !info.declared_bounds.contains(used) {
+                diagnostics.push(Diagnostic {
+                    line: info.line + 1,
+                    column: 0,
+                    end_column: line.len(),
+                    severity: RuleSeverity::Warning,
+                    rule_id: "missing_struct_provider_bound".to_string(),
+                    rule_name: "Missing Struct Provider Trait Bound".to_string(),
+                    category: RuleCategory::Provider,
+                    message: format!(
+                        "Struct '{}' uses trait '{}' in its methods but doesn't declare it in bounds.\n\nAdd '{}' to the struct bounds so it is required of the handlers that construct it.",
+                        info.path, used, used
+                    ),
+                    fix_template: Some(format!("Add '{}' to struct bounds", used)),
+                    source_snippet: Some(line.to_string()),
+                });
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Report provider calls that more than one trait could serve.
+    fn check_ambiguous_calls(&self, calls: &[AmbiguousCall], ctx: &FileContext) -> Vec<Diagnostic> {
+        calls
+            .iter()
+            .map(|call| {
+                let line = ctx.lines.get(call.line).copied().unwrap_or("");
+                let qualified: Vec<String> =
+                    call.candidates.iter().map(|t| format!("`{}::{}`", t, call.method)).collect();
+                Diagnostic {
+                    line: call.line + 1,
+                    column: call.col_range.0,
+                    end_column: call.col_range.1,
+                    severity: RuleSeverity::Warning,
+                    rule_id: "ambiguous_provider_call".to_string(),
+                    rule_name: "Ambiguous Provider Call".to_string(),
+                    category: RuleCategory::Provider,
+                    message: format!(
+                        "`{}` could be {}; the call is not attributed to a provider trait.\n\nCall it through its trait, or declare only one of them in bounds.",
+                        call.method,
+                        qualified.join(" or ")
+                    ),
+                    fix_template: Some(format!(
+                        "{}::{}(&provider, ..)",
+                        call.candidates[0], call.method
+                    )),
+                    source_snippet: Some(line.to_string()),
+                }
+            })
+            .collect()
+    }
+
+    /// Analyze trait usage patterns for additional diagnostics.
+    /// Uses regex for simple text-pattern checks that don't benefit from AST parsing.
+    fn analyze_trait_patterns(
+        &self, ctx: &FileContext, function_map: FunctionMap,
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let lines = &ctx.lines;
+
+        let config_get_re =
+            Regex::new(r"provider\.get\s*\([^)]+\)").expect("valid regex: config_get");
+        for (line_idx, line) in lines.iter().enumerate() {
+            if config_get_re.is_match(line)
+                && !line.contains("unwrap_or")
+                && !line.contains("ok_or")
+                && !line.contains('?')
+                && !line.contains(".await?")
+            {
+                diagnostics.push(Diagnostic {
+                    line: line_idx + 1,
+                    column: 0,
+                    end_column: line.len(),
+                    severity: RuleSeverity::Hint,
+                    rule_id: "config_error_handling".to_string(),
+                    rule_name: "Config::get Error Handling".to_string(),
+                    category: RuleCategory::Error,
+                    message: "Config::get returns Result - consider using `?` or `.ok_or_else(|| bad_request!(\"missing config\"))?`".to_string(),
+                    fix_template: Some("Add `?` or proper error handling".to_string()),
+                    source_snippet: Some(line.to_string()),
+                });
+            }
+        }
+
+        let set_no_ttl_re =
+            Regex::new(r"provider\.set\s*\([^,]+,[^,]+,\s*None\s*\)").expect("valid regex: set_no_ttl");
+        for (line_idx, line) in lines.iter().enumerate() {
+            if set_no_ttl_re.is_match(line) {
+                diagnostics.push(Diagnostic {
+                    line: line_idx + 1,
+                    column: 0,
+                    end_column: line.len(),
+                    severity: RuleSeverity::Warning,
+                    rule_id: "statestore_no_ttl".to_string(),
+                    rule_name: "StateStore::set Without TTL".to_string(),
+                    category: RuleCategory::Caching,
+                    message: "StateStore::set with None TTL - consider adding a TTL to prevent unbounded cache growth.\n\nExample: `Some(Duration::from_secs(3600))`".to_string(),
+                    fix_template: Some("Some(Duration::from_secs(3600))".to_string()),
+                    source_snippet: Some(line.to_string()),
+                });
+            }
+        }
+
+        let fetch_re =
+            Regex::new(r"provider\.fetch\s*\([^)]+\)\.await\?").expect("valid regex: fetch_context");
+        for (line_idx, line) in lines.iter().enumerate() {
+            if fetch_re.is_match(line) && !line.contains(".context(") {
+                diagnostics.push(Diagnostic {
+                    line: line_idx + 1,
+                    column: 0,
+                    end_column: line.len(),
+                    severity: RuleSeverity::Hint,
+                    rule_id: "fetch_error_context".to_string(),
+                    rule_name: "HttpRequest::fetch Error Context".to_string(),
+                    category: RuleCategory::Error,
+                    message: "Consider adding `.context(\"fetching from API\")` for better error messages.".to_string(),
+                    fix_template: Some("Add `.context(\"description\")` before `?`".to_string()),
+                    source_snippet: Some(line.to_string()),
+                });
+            }
+        }
+
+        self.check_function_trait_bounds(ctx, function_map, &mut diagnostics);
+        for fn_info in function_map.local.values() {
+            diagnostics.extend(self.check_ambiguous_calls(&fn_info.ambiguous_calls, ctx));
+        }
+
+        diagnostics
+    }
+
+    /// Check the helper functions of this file for proper trait bounds,
+    /// resolving their calls through `function_map`.
+    fn check_function_trait_bounds(
+        &self, ctx: &FileContext, function_map: FunctionMap, diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let lines = &ctx.lines;
+
+        for fn_info in function_map.local.values() {
+            let line = lines.get(fn_info.line).copied().unwrap_or("");
+
+            let used_traits = resolve_transitive_traits(
+                &fn_info.direct_trait_usage,
+                &fn_info.called_functions,
+                function_map,
+            );
+
+            for declared in &fn_info.direct_bounds {
+                if !used_traits.contains(declared)
+                    && !is_ambiguous_candidate(&fn_info.ambiguous_calls, declared)
+                {
+                    diagnostics.push(Diagnostic {
+                        line: fn_info.line + 1,
+                        column: 0,
+                        end_column: line.len(),
+                        severity: RuleSeverity::Warning,
+                        rule_id: "unused_fn_provider_bound".to_string(),
+                        rule_name: "Unused Function Provider Trait Bound".to_string(),
+                        category: RuleCategory::Provider,
+                        message: format!(
+                            "Unused provider trait bound `{}` in function `{}`.\n\nConsider removing it to keep bounds minimal.",
+                            declared, fn_info.path
+                        ),
+                        fix_template: Some(format!("Remove '{}' from function bounds", declared)),
+                        source_snippet: Some(line.to_string()),
+                    });
+                }
+            }
+
+            for used in &used_traits {
+                if !fn_info.declared_bounds.contains(used) && !fn_info.declared_bounds.is_empty() {
+                    diagnostics.push(Diagnostic {
+                        line: fn_info.line + 1,
+                        column: 0,
+                        end_column: line.len(),
+                        severity: RuleSeverity::Error,
+                        rule_id: "missing_fn_provider_bound".to_string(),
+                        rule_name: "Missing Function Provider Trait Bound".to_string(),
+                        category: RuleCategory::Provider,
+                        message: format!(
+                            "Missing provider trait bound `{}` in function `{}`.\n\nThe function uses `{}` methods but doesn't declare it in bounds.",
+                            used, fn_info.path, used
+                        ),
+                        fix_template: Some(format!("Add '{}' to function bounds", used)),
+                        source_snippet: Some(line.to_string()),
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl Default for SemanticAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_of(file: &syn::File) -> CallIndex<'static> {
+        let mut index = CallIndex::new(&ProviderCatalog::new());
+        index.add_files(&[(file, "")]);
+        index
+    }
+
+    #[test]
+    fn test_find_handler_implementations() {
+        let analyzer = SemanticAnalyzer::new();
+        let content = r#"
+impl<P: Config + HttpRequest> Handler<P> for MyRequest {
+    type Error = Error;
+    type Input = Vec<u8>;
+    type Output = MyResponse;
+
+    fn from_input(input: Self::Input) -> Result<Self> {
+        Ok(Self {})
+    }
+
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        let url = ctx.provider.get("API_URL")?;
+        let response = ctx.provider.fetch(request).await?;
+        Ok(Reply::ok(MyResponse {}))
+    }
+}
+"#;
+        let result = analyzer.analyze(content);
+        assert_eq!(result.handlers.len(), 1);
+
+        let handler = &result.handlers[0];
+        assert_eq!(handler.request_type, "MyRequest");
+        assert!(handler.declared_bounds.contains("Config"));
+        assert!(handler.declared_bounds.contains("HttpRequest"));
+        assert!(handler.used_traits.contains("Config"));
+        assert!(handler.used_traits.contains("HttpRequest"));
+    }
+
+    #[test]
+    fn test_detect_unused_bounds() {
+        let analyzer = SemanticAnalyzer::new();
+        let content = r#"
+impl<P: Config + HttpRequest + Publish> Handler<P> for MyRequest {
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        let url = ctx.provider.get("API_URL")?;
+        Ok(Reply::ok(MyResponse {}))
+    }
+}
+"#;
+        let result = analyzer.analyze(content);
+
+        let unused_warnings: Vec<_> =
+            result.diagnostics.iter().filter(|d| d.rule_id == "unused_provider_bound").collect();
+
+        assert!(!unused_warnings.is_empty());
+    }
+
+    #[test]
+    fn test_detect_missing_bounds() {
+        let analyzer = SemanticAnalyzer::new();
+        let content = r#"
+impl<P: Config> Handler<P> for CacheRequest {
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        let cached = ctx.provider.set("key", b"value", Some(60)).await?;
+        Ok(Reply::ok(cached))
+    }
+}
+"#;
+        let result = analyzer.analyze(content);
+
+        let missing_errors: Vec<_> =
+            result.diagnostics.iter().filter(|d| d.rule_id == "missing_provider_bound").collect();
+
+        assert!(!missing_errors.is_empty());
+    }
+
+    #[test]
+    fn test_transitive_trait_detection_via_function_delegation() {
+        let analyzer = SemanticAnalyzer::new();
+        let content = r#"
+async fn fetch_data<P: HttpRequest>(provider: &P, url: &str) -> Result<Response> {
+    provider.fetch(url).await
+}
+
+impl<P: Config + HttpRequest> Handler<P> for DelegatingRequest {
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        let url = ctx.provider.get("API_URL")?;
+        let response = fetch_data(&ctx.provider, &url).await?;
+        Ok(Reply::ok(response))
+    }
+}
+"#;
+        let result = analyzer.analyze(content);
+
+        let handler = &result.handlers[0];
+
+        assert!(
+            handler.used_traits.contains("HttpRequest"),
+            "HttpRequest should be detected via transitive delegation to fetch_data"
+        );
+
+        let unused_http: Vec<_> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.rule_id == "unused_provider_bound" && d.message.contains("HttpRequest"))
+            .collect();
+
+        assert!(
+            unused_http.is_empty(),
+            "HttpRequest should not be flagged as unused when used transitively: {:?}",
+            unused_http
+        );
+    }
+
+    #[test]
+    fn test_transitive_trait_detection_chain() {
+        let analyzer = SemanticAnalyzer::new();
+        let content = r#"
+async fn send_notification<P: Publish>(provider: &P, msg: &str) -> Result<()> {
+    provider.send(msg).await
+}
+
+async fn notify_users<P: Publish>(provider: &P) -> Result<()> {
+    send_notification(provider, "Hello").await
+}
+
+impl<P: Config + Publish> Handler<P> for ChainedRequest {
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        let _ = ctx.provider.get("NOTIFY")?;
+        notify_users(&ctx.provider).await?;
+        Ok(Reply::ok(()))
+    }
+}
+"#;
+        let result = analyzer.analyze(content);
+
+        let handler = &result.handlers[0];
+
+        assert!(
+            handler.used_traits.contains("Publish"),
+            "Publish should be detected via chained delegation: notify_users -> send_notification"
+        );
+
+        let unused_pub: Vec<_> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.rule_id == "unused_provider_bound" && d.message.contains("Publish"))
+            .collect();
+
+        assert!(
+            unused_pub.is_empty(),
+            "Publish should not be flagged as unused when used in delegation chain"
+        );
+    }
+
+    #[test]
+    fn test_shared_method_attributed_by_bounds_in_scope() {
+        let analyzer = SemanticAnalyzer::new();
+        let content = r#"
+impl<P: Config> Handler<P> for ConfigRequest {
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        let key = ctx.provider.get("API_KEY").await?;
+        Ok(Reply::ok(key))
+    }
+}
+
+impl<P: StateStore> Handler<P> for StateRequest {
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        let cached = ctx.provider.get("key").await?;
+        Ok(Reply::ok(cached))
+    }
+}
+
+async fn cached<P: StateStore>(provider: &P) -> Result<()> {
+    let pending = provider.get("key");
+    pending.await?;
+    Ok(())
+}
+"#;
+        let result = analyzer.analyze(content);
+
+        assert_eq!(result.handlers[0].used_traits, HashSet::from(["Config".to_string()]));
+        assert_eq!(result.handlers[1].used_traits, HashSet::from(["StateStore".to_string()]));
+        let flagged: Vec<_> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.category == RuleCategory::Provider)
+            .map(|d| d.rule_id.as_str())
+            .collect();
+        assert!(flagged.is_empty(), "unexpected diagnostics: {:?}", flagged);
+    }
+
+    #[test]
+    fn test_ambiguous_shared_method_reported() {
+        let analyzer = SemanticAnalyzer::new();
+        let content = r#"
+impl<P: Config + StateStore> Handler<P> for BothRequest {
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        let value = ctx.provider.get("key").await?;
+        let cached = StateStore::get(&ctx.provider, "key").await?;
+        Ok(Reply::ok(value))
+    }
+}
+"#;
+        let result = analyzer.analyze(content);
+
+        let ambiguous: Vec<_> =
+            result.diagnostics.iter().filter(|d| d.rule_id == "ambiguous_provider_call").collect();
+        assert_eq!(ambiguous.len(), 1);
+        assert_eq!(ambiguous[0].line, 4);
+        assert!(ambiguous[0].message.contains("`Config::get` or `StateStore::get`"));
+
+        // Neither candidate is reported as unused
+        assert!(result.diagnostics.iter().all(|d| d.rule_id != "unused_provider_bound"));
+        assert!(result.handlers[0].used_traits.contains("StateStore"));
+        assert!(!result.handlers[0].used_traits.contains("Config"));
+    }
+
+    #[test]
+    fn test_shared_method_told_apart_by_result_type() {
+        let analyzer = SemanticAnalyzer::new();
+        let content = r#"
+impl<P: Config + StateStore> Handler<P> for TypedRequest {
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        let url: String = ctx.provider.get("URL").await?;
+        let c: Option<Vec<u8>> = ctx.provider.get("k").await?;
+        Ok(Reply::ok(url))
+    }
+}
+
+impl<P: Config + StateStore> Handler<P> for CachedRequest {
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        if let Some(bytes) = ctx.provider.get("k").await? {
+            return Ok(Reply::ok(bytes));
+        }
+        let fresh = ctx.provider.get("k2").await?.is_none();
+        Ok(Reply::ok(fresh))
+    }
+}
+"#;
+        let result = analyzer.analyze(content);
+
+        assert!(
+            result.diagnostics.iter().all(|d| d.rule_id != "ambiguous_provider_call"),
+            "unexpected diagnostics: {:?}",
+            result.diagnostics.iter().map(|d| &d.rule_id).collect::<Vec<_>>()
+        );
+        let typed = &result.handlers[0];
+        assert!(typed.used_traits.contains("Config"));
+        assert!(typed.used_traits.contains("StateStore"));
+
+        // Option-style uses make both calls StateStore::get, so Config is unused
+        let cached = &result.handlers[1];
+        assert_eq!(cached.used_traits, HashSet::from(["StateStore".to_string()]));
+        assert!(
+            result
+                .diagnostics
+                .iter()
+                .any(|d| d.rule_id == "unused_provider_bound" && d.message.contains("Config"))
+        );
+    }
+
+    #[test]
+    fn test_provider_receivers_followed_through_bindings() {
+        let analyzer = SemanticAnalyzer::new();
+        let content = r#"
+impl<P: HttpRequest + Publish + StateStore> Handler<P> for AliasRequest {
+    async fn handle(self, context: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        let p = &context.provider;
+        let response = p.fetch(request).await?;
+        let (client, topic) = (&context.provider, "events");
+        client.send(topic, b"m").await?;
+        let Context { provider: store, .. } = context;
+        store.delete("key").await?;
+        Ok(Reply::ok(response))
+    }
+}
+
+async fn load<P: TableStore>(prov: &P) -> Result<()> {
+    prov.query("db", "q", vec![]).await
+}
+
+async fn token(identity: &impl Identity) -> Result<String> {
+    identity.access_token("scope").await
+}
+"#;
+        let result = analyzer.analyze(content);
+
+        let handler = &result.handlers[0];
+        for used in ["HttpRequest", "Publish", "StateStore"] {
+            assert!(handler.direct_trait_usage.contains(used), "{} not detected", used);
+        }
+        assert!(result.diagnostics.iter().all(|d| d.category != RuleCategory::Provider));
+
+        let file = syn::parse_file(content).expect("valid syntax");
+        let functions = extract_functions(&file, "", &index_of(&file));
+        assert!(functions["load"].direct_trait_usage.contains("TableStore"));
+        assert!(functions["token"].direct_trait_usage.contains("Identity"));
+    }
+
+    #[test]
+    fn test_shadowed_provider_binding_is_not_a_receiver() {
+        let analyzer = SemanticAnalyzer::new();
+        let content = r#"
+impl<P: HttpRequest> Handler<P> for ShadowRequest {
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        let p = &ctx.provider;
+        {
+            let p = Client::new();
+            p.fetch(request).await?;
+        }
+        let p = Client::new();
+        p.fetch(request).await?;
+        Ok(Reply::ok(()))
+    }
+}
+"#;
+        let result = analyzer.analyze(content);
+
+        assert!(result.handlers[0].direct_trait_usage.is_empty());
+        assert!(result.diagnostics.iter().any(|d| d.rule_id == "unused_provider_bound"));
+
+        // The names `provider` and `ctx` carry no meaning of their own
+        let content = r#"
+impl<P: Config> Handler<P> for MailRequest {
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        let url = ctx.provider.get("URL").await?;
+        let provider = mailer::Client::new(url);
+        provider.send("t", &m).await?;
+        Ok(Reply::ok(()))
+    }
+}
+
+async fn notify(provider: mailer::Client, ctx: Request) -> Result<()> {
+    provider.send("t", &ctx.message).await
+}
+"#;
+        let result = analyzer.analyze(content);
+
+        assert_eq!(result.handlers[0].direct_trait_usage, HashSet::from(["Config".to_string()]));
+        assert!(
+            result.diagnostics.iter().all(|d| d.category != RuleCategory::Provider),
+            "{:?}",
+            result.diagnostics
+        );
+        let file = syn::parse_file(content).expect("valid syntax");
+        assert!(!extract_functions(&file, "", &index_of(&file)).contains_key("notify"));
+    }
+
+    #[test]
+    fn test_provider_generic_struct_bounds_propagate() {
+        let content = r#"
+struct Catalog<'a, P: HttpRequest> {
+    provider: &'a P,
+}
+
+impl<P: HttpRequest> Catalog<'_, P> {
+    async fn list(&self) -> Result<Vec<Item>> {
+        self.provider.fetch(request).await
+    }
+}
+
+struct Plain<Q> {
+    client: Q,
+}
+
+impl<Q: Publish> Plain<Q> {
+    async fn notify(&self) -> Result<()> {
+        self.client.send("topic", b"m").await
+    }
+}
+
+impl<P: Config + HttpRequest> Handler<P> for ListRequest {
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        let _ = ctx.provider.get("KEY").await?;
+        let items = Catalog { provider: &ctx.provider }.list().await?;
+        Ok(Reply::ok(items))
+    }
+}
+
+impl<P: Config> Handler<P> for BrokenRequest {
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        let _ = ctx.provider.get("KEY").await?;
+        let catalog = Catalog { provider: &ctx.provider };
+        Ok(Reply::ok(()))
+    }
+}
+"#;
+        let file = syn::parse_file(content).expect("valid syntax");
+        let functions = extract_functions(&file, "", &index_of(&file));
+        assert!(functions["Catalog::list"].direct_trait_usage.contains("HttpRequest"));
+        assert!(functions["Plain::notify"].direct_trait_usage.contains("Publish"));
+
+        let result = SemanticAnalyzer::new().analyze(content);
+        assert!(result.handlers[0].used_traits.contains("HttpRequest"));
+        let provider: Vec<_> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.category == RuleCategory::Provider)
+            .map(|d| (d.rule_id.as_str(), d.line))
+            .collect();
+        assert_eq!(provider, vec![("missing_provider_bound", 30)]);
+    }
+
+    #[test]
+    fn test_struct_bounds_checked_against_methods() {
+        let content = r#"
+struct Client<P: HttpRequest + Publish> {
+    inner: P,
+}
+
+impl<P: HttpRequest + Publish + StateStore> Client<P> {
+    async fn run(&self) -> Result<()> {
+        self.inner.fetch(request).await?;
+        self.inner.delete("key").await
+    }
+}
+"#;
+        let result = SemanticAnalyzer::new().analyze(content);
+
+        let info = &result.provider_types[0];
+        assert_eq!(info.path, "Client");
+        assert_eq!(info.methods, vec!["Client::run"]);
+        let mut flagged: Vec<_> = result
+            .diagnostics
+            .iter()
+            .map(|d| (d.rule_id.as_str(), d.line, d.message.contains("Publish")))
+            .collect();
+        flagged.sort_unstable();
+        assert_eq!(
+            flagged,
+            vec![
+                ("missing_struct_provider_bound", 2, false),
+                ("unused_struct_provider_bound", 2, true)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_analyze_statestore_no_ttl() {
+        let analyzer = SemanticAnalyzer::new();
+        let content = r#"
+async fn cache_it<P: StateStore>(provider: &P) -> Result<()> {
+    provider.set("key", b"value", None).await?;
+    Ok(())
+}
+"#;
+        let result = analyzer.analyze(content);
+        let ttl_warnings: Vec<_> =
+            result.diagnostics.iter().filter(|d| d.rule_id == "statestore_no_ttl").collect();
+        assert!(
+            !ttl_warnings.is_empty(),
+            "Should warn about StateStore::set with None TTL"
+        );
+    }
+
+    #[test]
+    fn test_extract_provider_bounds_inline() {
+        let generics: syn::Generics = syn::parse_str("< P : Config + HttpRequest >").unwrap();
+        let bounds = extract_provider_bounds(&ProviderCatalog::new(), &generics);
+        assert!(bounds.contains("Config"));
+        assert!(bounds.contains("HttpRequest"));
+        assert_eq!(bounds.len(), 2);
+    }
+
+    #[test]
+    fn test_extract_provider_bounds_where_clause() {
+        let item: syn::ItemFn = syn::parse_str(
+            "fn foo<P>() where P: Config + StateStore {}",
+        )
+        .unwrap();
+        let bounds = extract_provider_bounds(&ProviderCatalog::new(), &item.sig.generics);
+        assert!(bounds.contains("Config"));
+        assert!(bounds.contains("StateStore"));
+    }
+
+    #[test]
+    fn test_extract_provider_bounds_filters_non_provider() {
+        let generics: syn::Generics =
+            syn::parse_str("< P : Config + Send + Sync + Debug >").unwrap();
+        let bounds = extract_provider_bounds(&ProviderCatalog::new(), &generics);
+        assert!(bounds.contains("Config"));
+        assert!(!bounds.contains("Send"));
+        assert!(!bounds.contains("Debug"));
+        assert_eq!(bounds.len(), 1);
+    }
+
+    #[test]
+    fn test_graceful_fallback_on_invalid_syntax() {
+        let analyzer = SemanticAnalyzer::new();
+        let content = "this is not valid rust {{{{";
+        let result = analyzer.analyze(content);
+        assert!(result.handlers.is_empty());
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].rule_id, "parse_error");
+        assert_eq!(result.diagnostics[0].line, 1);
+    }
+
+    #[test]
+    fn test_partial_recovery_keeps_handler_checks() {
+        let analyzer = SemanticAnalyzer::new();
+        let content = r#"
+impl<P: Config + Publish> Handler<P> for MyRequest {
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        let url = Config::get(ctx.provider, "API_URL").await?;
+        Ok(Reply::ok(url))
+    }
+}
+
+fn half_typed(x: u32 {
+    x +
+}
+"#;
+        let result = analyzer.analyze(content);
+        assert_eq!(result.handlers.len(), 1);
+
+        let parse_error = result.diagnostics.iter().find(|d| d.rule_id == "parse_error");
+        assert_eq!(parse_error.map(|d| d.line), Some(9));
+        assert!(
+            result
+                .diagnostics
+                .iter()
+                .any(|d| d.rule_id == "unused_provider_bound" && d.message.contains("Publish"))
+        );
+    }
+
+    #[test]
+    fn test_handler_missing_bounds_diagnostic() {
+        let analyzer = SemanticAnalyzer::new();
+        let content = r#"
+impl<P> Handler<P> for NoBoundsRequest {
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        Ok(Reply::ok(()))
+    }
+}
+"#;
+        let result = analyzer.analyze(content);
+        let missing: Vec<_> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.rule_id == "handler_missing_bounds")
+            .collect();
+        assert!(
+            !missing.is_empty(),
+            "Should warn about handler with no provider bounds"
+        );
+    }
+
+    #[test]
+    fn test_well_formed_handler_has_no_shape_issues() {
+        let analyzer = SemanticAnalyzer::new();
+        let content = r#"
+impl<P: Config> Handler<P> for GoodRequest {
+    type Error = omnia_sdk::Error;
+    type Input = Vec<u8>;
+    type Output = GoodResponse;
+
+    fn from_input(input: Self::Input) -> Result<Self> {
+        serde_json::from_slice(&input).context("deserializing")
+    }
+
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        let url = Config::get(ctx.provider, "URL").await?;
+        Ok(Reply::ok(GoodResponse { url }))
     }
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
+"#;
+        let result = analyzer.analyze(content);
+        assert!(
+            result.handlers[0].shape_issues.is_empty(),
+            "{:?}",
+            result.handlers[0].shape_issues
+        );
+        assert_eq!(result.handlers[0].provider_param.as_deref(), Some("P"));
+    }
 
     #[test]
-    fn test_find_handler_implementations() {
+    fn test_handler_shape_issues_at_item_spans() {
         let analyzer = SemanticAnalyzer::new();
         let content = r#"
-impl<P: Config + HttpRequest> Handler<P> for MyRequest {
-    type Error = Error;
+impl Handler<Provider> for BadRequest {
+    type Error = anyhow::Error;
     type Input = Vec<u8>;
-    type Output = MyResponse;
 
-    fn from_input(input: Self::Input) -> Result<Self> {
-        Ok(Self {})
+    fn from_input(input: Self::Input) -> Self {
+        todo!()
     }
 
-    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
-        let url = ctx.provider.get("API_URL")?;
-        let response = ctx.provider.fetch(request).await?;
-        Ok(Reply::ok(MyResponse {}))
+    fn handle(&self, provider: &Provider) -> Result<Reply<String>> {
+        todo!()
     }
 }
 "#;
         let result = analyzer.analyze(content);
-        assert_eq!(result.handlers.len(), 1);
-
-        let handler = &result.handlers[0];
-        assert_eq!(handler.request_type, "MyRequest");
-        assert!(handler.declared_bounds.contains("Config"));
-        assert!(handler.declared_bounds.contains("HttpRequest"));
-        assert!(handler.used_traits.contains("Config"));
-        assert!(handler.used_traits.contains("HttpRequest"));
+        let found: Vec<(&str, usize, usize, usize)> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.category == RuleCategory::Handler)
+            .map(|d| (d.rule_id.as_str(), d.line, d.column, d.end_column))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("handler_generic_p", 2, 13, 21),
+                ("handler_error_type", 3, 4, 31),
+                ("handler_from_input_result", 6, 38, 45),
+                ("handler_async_handle", 10, 4, 13),
+                ("handler_handle_signature", 10, 13, 41),
+                ("handler_output_type", 2, 0, 37),
+            ]
+        );
+        // A concrete provider type has no bounds to check
+        assert!(result.diagnostics.iter().all(|d| d.rule_id != "handler_missing_bounds"));
     }
 
     #[test]
-    fn test_detect_unused_bounds() {
+    fn test_handler_input_should_be_bytes() {
         let analyzer = SemanticAnalyzer::new();
         let content = r#"
-impl<P: Config + HttpRequest + Publisher> Handler<P> for MyRequest {
-    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
-        let url = ctx.provider.get("API_URL")?;
-        Ok(Reply::ok(MyResponse {}))
-    }
+impl<P: Config> Handler<P> for TextRequest {
+    type Input = String;
+    type Output = Resp;
+}
+
+impl<P: Config> Handler<P> for BytesRequest {
+    type Input = std::vec::Vec<u8>;
+    type Output = Resp;
 }
 "#;
         let result = analyzer.analyze(content);
+        let found: Vec<(&str, usize, usize, usize, RuleSeverity)> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.category == RuleCategory::Handler)
+            .map(|d| (d.rule_id.as_str(), d.line, d.column, d.end_column, d.severity))
+            .collect();
+        assert_eq!(found, vec![("handler_input_vec_u8", 3, 4, 24, RuleSeverity::Info)]);
+    }
 
-        let unused_warnings: Vec<_> =
-            result.diagnostics.iter().filter(|d| d.rule_id == "unused_provider_bound").collect();
+    #[test]
+    fn test_handler_error_resolved_through_imports() {
+        let analyzer = SemanticAnalyzer::new();
+        let error_type_lines = |content: &str| -> Vec<usize> {
+            let result = analyzer.analyze(content);
+            result
+                .diagnostics
+                .iter()
+                .filter(|d| d.rule_id == "handler_error_type")
+                .map(|d| d.line)
+                .collect()
+        };
+
+        let content = r#"
+use anyhow::Error;
+
+impl<P: Config> Handler<P> for AnyhowRequest {
+    type Error = Error;
+    type Output = Resp;
+}
+"#;
+        assert_eq!(error_type_lines(content), vec![5]);
+
+        let content = r#"
+use omnia_sdk::Error;
+use omnia_sdk::Error as SdkError;
+
+impl<P: Config> Handler<P> for SdkRequest {
+    type Error = Error;
+    type Output = Resp;
+}
+
+impl<P: Config> Handler<P> for RenamedRequest {
+    type Error = SdkError;
+    type Output = Resp;
+}
+
+impl<P: Config> Handler<P> for QualifiedRequest {
+    type Error = omnia::Error;
+    type Output = Resp;
+}
+"#;
+        assert!(error_type_lines(content).is_empty());
+
+        // Through a glob, unless the module declares its own `Error`
+        let content = r#"
+use omnia_sdk::*;
+
+impl<P: Config> Handler<P> for GlobRequest {
+    type Error = Error;
+    type Output = Resp;
+}
+"#;
+        assert!(error_type_lines(content).is_empty());
+        let content = r#"
+use omnia_sdk::*;
+
+pub enum Error {}
+
+impl<P: Config> Handler<P> for LocalRequest {
+    type Error = Error;
+    type Output = Resp;
+}
 
-        assert!(unused_warnings.len() >= 1);
+impl<P: Config> Handler<P> for UnimportedRequest {
+    type Error = Failure;
+    type Output = Resp;
+}
+"#;
+        assert_eq!(error_type_lines(content), vec![7, 12]);
     }
 
     #[test]
-    fn test_detect_missing_bounds() {
+    fn test_handle_must_take_context_of_provider() {
         let analyzer = SemanticAnalyzer::new();
         let content = r#"
-impl<P: Config> Handler<P> for CacheRequest {
-    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
-        let cached = ctx.provider.get("key").await?;
-        Ok(Reply::ok(cached))
+impl<P: Config> Handler<P> for Req {
+    type Output = Resp;
+    async fn handle(self, ctx: Context<'_, Other>) -> Result<Reply<Self::Output>> {
+        todo!()
     }
 }
 "#;
         let result = analyzer.analyze(content);
-
-        let missing_errors: Vec<_> =
-            result.diagnostics.iter().filter(|d| d.rule_id == "missing_provider_bound").collect();
-
-        assert!(missing_errors.len() >= 1);
+        let ids: Vec<&str> = result.handlers[0].shape_issues.iter().map(|i| i.rule_id).collect();
+        assert_eq!(ids, vec!["handler_handle_signature"]);
     }
 
     #[test]
-    fn test_transitive_trait_detection_via_function_delegation() {
+    fn test_serde_derives_resolve_aliases_and_wrappers() {
         let analyzer = SemanticAnalyzer::new();
         let content = r#"
-async fn fetch_data<P: HttpRequest>(provider: &P, url: &str) -> Result<Response> {
-    provider.fetch(url).await
+#[derive(Debug, Deserialize)]
+pub struct RawRequest {
+    id: String,
 }
 
-impl<P: Config + HttpRequest> Handler<P> for DelegatingRequest {
-    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
-        let url = ctx.provider.get("API_URL")?;
-        let response = fetch_data(&ctx.provider, &url).await?;
-        Ok(Reply::ok(response))
-    }
+pub type GetRequest = RawRequest;
+
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Item {
+    id: String,
+}
+
+#[derive(Debug)]
+pub struct Missing {
+    id: String,
+}
+
+impl<P: Config> Handler<P> for GetRequest {
+    type Output = Vec<Item>;
+}
+
+impl<P: Config> Handler<P> for Missing {
+    type Output = Option<Missing>;
+}
+
+impl<P: Config> Handler<P> for External {
+    type Output = serde_json::Value;
 }
 "#;
         let result = analyzer.analyze(content);
+        let found: Vec<(&str, usize, usize)> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.rule_id.starts_with("handler_serde"))
+            .map(|d| (d.rule_id.as_str(), d.line, d.column))
+            .collect();
+        assert_eq!(
+            found,
+            vec![("handler_serde_deserialize", 15, 11), ("handler_serde_serialize", 15, 11)]
+        );
+    }
 
-        let handler = &result.handlers[0];
+    #[test]
+    fn test_serde_container_conversions() {
+        let analyzer = SemanticAnalyzer::new();
+        let content = r#"
+#[derive(Deserialize)]
+#[serde(try_from = "Wire", rename_all = "camelCase")]
+pub struct Validated {
+    id: String,
+}
 
-        assert!(
-            handler.used_traits.contains("HttpRequest"),
-            "HttpRequest should be detected via transitive delegation to fetch_data"
-        );
+pub struct Wire {
+    id: String,
+}
 
-        let unused_http: Vec<_> = result
+#[derive(Serialize)]
+#[serde(into = "Shown")]
+pub struct Answer;
+
+#[derive(Serialize)]
+pub struct Shown;
+
+impl<P: Config> Handler<P> for Validated {
+    type Output = Answer;
+}
+"#;
+        let result = analyzer.analyze(content);
+        let found: Vec<(&str, usize)> = result
             .diagnostics
             .iter()
-            .filter(|d| d.rule_id == "unused_provider_bound" && d.message.contains("HttpRequest"))
+            .filter(|d| d.rule_id.starts_with("handler_serde"))
+            .map(|d| (d.rule_id.as_str(), d.line))
             .collect();
+        assert_eq!(found, vec![("handler_serde_deserialize", 8)]);
+    }
+
+    #[test]
+    fn test_serde_impls_count_as_derives() {
+        let analyzer = SemanticAnalyzer::new();
+        let content = r#"
+pub struct Req {
+    id: String,
+}
+
+impl<'de> serde::Deserialize<'de> for Req {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        todo!()
+    }
+}
+
+pub struct Out;
+
+mod wire {
+    impl Serialize for super::Out {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            todo!()
+        }
+    }
+}
 
+impl<P: Config> Handler<P> for Req {
+    type Output = Vec<Out>;
+}
+"#;
+        let result = analyzer.analyze(content);
         assert!(
-            unused_http.is_empty(),
-            "HttpRequest should not be flagged as unused when used transitively: {:?}",
-            unused_http
+            result.diagnostics.iter().all(|d| !d.rule_id.starts_with("handler_serde")),
+            "{:?}",
+            result.diagnostics
         );
     }
 
     #[test]
-    fn test_transitive_trait_detection_chain() {
+    fn test_handlers_and_helpers_in_nested_modules() {
         let analyzer = SemanticAnalyzer::new();
         let content = r#"
-async fn send_notification<P: Publisher>(provider: &P, msg: &str) -> Result<()> {
-    provider.send(msg).await
-}
+mod handlers {
+    async fn load<P: TableStore>(provider: &P) -> Result<()> {
+        provider.query("SELECT 1").await
+    }
 
-async fn notify_users<P: Publisher>(provider: &P) -> Result<()> {
-    send_notification(provider, "Hello").await
+    impl<P: Config> Handler<P> for ListRequest {
+        async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+            load(&ctx.provider).await?;
+            Ok(Reply::ok(()))
+        }
+    }
 }
 
-impl<P: Config + Publisher> Handler<P> for ChainedRequest {
-    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
-        let _ = ctx.provider.get("NOTIFY")?;
-        notify_users(&ctx.provider).await?;
-        Ok(Reply::ok(()))
+mod other {
+    async fn load<P: Publish>(provider: &P) -> Result<()> {
+        Ok(())
     }
 }
 "#;
         let result = analyzer.analyze(content);
+        assert_eq!(result.handlers.len(), 1);
 
         let handler = &result.handlers[0];
+        assert!(handler.called_functions.contains("handlers::load"));
+        assert!(handler.used_traits.contains("TableStore"));
+        assert!(!handler.used_traits.contains("Publish"));
 
-        assert!(
-            handler.used_traits.contains("Publisher"),
-            "Publisher should be detected via chained delegation: notify_users -> send_notification"
-        );
-
-        let unused_pub: Vec<_> = result
+        let mut found: Vec<(&str, usize)> = result
             .diagnostics
             .iter()
-            .filter(|d| d.rule_id == "unused_provider_bound" && d.message.contains("Publisher"))
+            .filter(|d| d.category == RuleCategory::Provider)
+            .map(|d| (d.rule_id.as_str(), d.line))
             .collect();
-
-        assert!(
-            unused_pub.is_empty(),
-            "Publisher should not be flagged as unused when used in delegation chain"
+        found.sort_unstable();
+        assert_eq!(
+            found,
+            vec![
+                ("missing_provider_bound", 7),
+                ("unused_fn_provider_bound", 16),
+                ("unused_provider_bound", 7),
+            ]
         );
     }
 
     #[test]
-    fn test_analyze_statestore_no_ttl() {
-        let analyzer = SemanticAnalyzer::new();
+    fn test_impl_and_trait_default_methods_are_helpers() {
         let content = r#"
-async fn cache_it<P: StateStore>(provider: &P) -> Result<()> {
-    provider.set("key", b"value", None).await?;
-    Ok(())
+impl Repo {
+    async fn load<P: TableStore + Config>(provider: &P) -> Result<()> {
+        provider.query("SELECT 1").await
+    }
+}
+
+trait Notify {
+    async fn notify<P: Publish>(provider: &P) -> Result<()> {
+        provider.send("topic", b"hi").await
+    }
+
+    async fn required<P: Publish>(provider: &P) -> Result<()>;
 }
 "#;
-        let result = analyzer.analyze(content);
-        let ttl_warnings: Vec<_> =
-            result.diagnostics.iter().filter(|d| d.rule_id == "statestore_no_ttl").collect();
-        assert!(
-            !ttl_warnings.is_empty(),
-            "Should warn about StateStore::set with None TTL"
-        );
+        let file = syn::parse_file(content).expect("valid syntax");
+        let functions = extract_functions(&file, "", &index_of(&file));
+        let mut paths: Vec<&str> = functions.keys().map(String::as_str).collect();
+        paths.sort_unstable();
+        assert_eq!(paths, vec!["Notify::notify", "Repo::load"]);
+        assert!(functions["Notify::notify"].direct_trait_usage.contains("Publish"));
+
+        let result = SemanticAnalyzer::new().analyze(content);
+        let unused: Vec<&str> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.rule_id == "unused_fn_provider_bound")
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(unused.len(), 1);
+        assert!(unused[0].contains("`Config` in function `Repo::load`"));
     }
 
     #[test]
-    fn test_extract_provider_bounds_inline() {
-        let generics: syn::Generics = syn::parse_str("< P : Config + HttpRequest >").unwrap();
-        let bounds = extract_provider_bounds(&generics);
-        assert!(bounds.contains("Config"));
-        assert!(bounds.contains("HttpRequest"));
-        assert_eq!(bounds.len(), 2);
+    fn test_calls_resolve_through_paths_and_imports() {
+        let content = r#"
+mod helpers {
+    pub async fn fetch_data<P: HttpRequest>(provider: &P) -> Result<()> {
+        provider.fetch("url").await
     }
 
-    #[test]
-    fn test_extract_provider_bounds_where_clause() {
-        let item: syn::ItemFn = syn::parse_str(
-            "fn foo<P>() where P: Config + StateStore {}",
-        )
-        .unwrap();
-        let bounds = extract_provider_bounds(&item.sig.generics);
-        assert!(bounds.contains("Config"));
-        assert!(bounds.contains("StateStore"));
+    pub mod store {
+        pub async fn save<P: StateStore>(provider: &P) -> Result<()> {
+            provider.set("k", b"v", None).await
+        }
     }
+}
 
-    #[test]
-    fn test_extract_provider_bounds_filters_non_provider() {
-        let generics: syn::Generics =
-            syn::parse_str("< P : Config + Send + Sync + Debug >").unwrap();
-        let bounds = extract_provider_bounds(&generics);
-        assert!(bounds.contains("Config"));
-        assert!(!bounds.contains("Send"));
-        assert!(!bounds.contains("Debug"));
-        assert_eq!(bounds.len(), 1);
+mod api {
+    use crate::helpers::fetch_data as fetch;
+    use super::helpers::store::*;
+
+    pub async fn run<P: HttpRequest + StateStore + Publish>(provider: &P) -> Result<()> {
+        fetch(provider).await?;
+        save(provider).await?;
+        crate::helpers::fetch_data(provider).await
+    }
+}
+"#;
+        let file = syn::parse_file(content).expect("valid syntax");
+        let functions = extract_functions(&file, "", &index_of(&file));
+        let mut calls: Vec<&str> =
+            functions["api::run"].called_functions.iter().map(String::as_str).collect();
+        calls.sort_unstable();
+        assert_eq!(calls, vec!["helpers::fetch_data", "helpers::store::save"]);
+
+        let result = SemanticAnalyzer::new().analyze(content);
+        let unused: Vec<&str> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.rule_id == "unused_fn_provider_bound")
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(unused.len(), 1);
+        assert!(unused[0].contains("`Publish` in function `api::run`"));
     }
 
     #[test]
-    fn test_graceful_fallback_on_invalid_syntax() {
+    fn test_calls_resolve_through_self_and_fields() {
         let analyzer = SemanticAnalyzer::new();
-        let content = "this is not valid rust {{{{";
+        let content = r#"
+struct Repo;
+
+impl Repo {
+    async fn load<P: TableStore>(&self, provider: &P) -> Result<()> {
+        provider.query("SELECT 1").await
+    }
+}
+
+struct Service {
+    repo: Repo,
+}
+
+impl Service {
+    async fn list<P: TableStore>(&self, provider: &P) -> Result<()> {
+        self.repo.load(provider).await
+    }
+}
+
+impl ListRequest {
+    async fn notify<P: Publish>(provider: &P) -> Result<()> {
+        provider.send("topic", b"done").await
+    }
+}
+
+impl<P: Publish + TableStore> Handler<P> for ListRequest {
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        Service { repo: Repo }.list(&ctx.provider).await?;
+        Self::notify(&ctx.provider).await?;
+        Ok(Reply::ok(()))
+    }
+}
+"#;
         let result = analyzer.analyze(content);
-        assert!(result.handlers.is_empty());
-        assert!(result.diagnostics.is_empty());
+        let handler = &result.handlers[0];
+        let mut calls: Vec<&str> = handler.called_functions.iter().map(String::as_str).collect();
+        calls.sort_unstable();
+        assert_eq!(calls, vec!["ListRequest::notify", "Service::list"]);
+        assert!(handler.used_traits.contains("TableStore"));
+        assert!(handler.used_traits.contains("Publish"));
+        assert!(
+            !result.diagnostics.iter().any(|d| d.category == RuleCategory::Provider),
+            "{:?}",
+            result.diagnostics
+        );
     }
 
     #[test]
-    fn test_handler_missing_bounds_diagnostic() {
+    fn test_composite_provider_traits_expand_to_supertraits() {
         let analyzer = SemanticAnalyzer::new();
         let content = r#"
-impl<P> Handler<P> for NoBoundsRequest {
+pub trait Provider: Config + HttpRequest + StateStore {}
+impl<T: Config + HttpRequest + StateStore> Provider for T {}
+
+pub trait Full: Provider + omnia_sdk::Publish {}
+
+impl<P: Provider> Handler<P> for FetchRequest {
+    async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        let body = ctx.provider.fetch(request).await?;
+        Ok(Reply::ok(body))
+    }
+}
+
+impl<P> Handler<P> for PublishRequest
+where
+    P: Full,
+{
     async fn handle(self, ctx: Context<'_, P>) -> Result<Reply<Self::Output>> {
+        ctx.provider.send("topic", b"hi").await?;
+        ctx.provider.access_token().await?;
         Ok(Reply::ok(()))
     }
 }
 "#;
         let result = analyzer.analyze(content);
-        let missing: Vec<_> = result
+        let fetch = &result.handlers[0];
+        let mut declared: Vec<&str> = fetch.declared_bounds.iter().map(String::as_str).collect();
+        declared.sort_unstable();
+        assert_eq!(declared, vec!["Config", "HttpRequest", "StateStore"]);
+        assert!(fetch.direct_bounds.is_empty());
+        assert_eq!(result.handlers[1].declared_bounds.len(), 4);
+
+        let found: Vec<(&str, usize)> = result
             .diagnostics
             .iter()
-            .filter(|d| d.rule_id == "handler_missing_bounds")
+            .filter(|d| {
+                d.category == RuleCategory::Provider || d.rule_id == "handler_missing_bounds"
+            })
+            .map(|d| (d.rule_id.as_str(), d.line))
             .collect();
-        assert!(
-            !missing.is_empty(),
-            "Should warn about handler with no provider bounds"
-        );
+        assert_eq!(found, vec![("missing_provider_bound", 14)]);
+        assert!(result.diagnostics.iter().any(|d| d.message.contains("'Identity'")));
     }
 }
@@ -0,0 +1,825 @@
+//! Inline suppression directives (`#[omnia::allow(...)]`, `#[omnia::expect(...)]`).
+//!
+//! Directives are found with a regex scan so they work on any file, then
+//! attached to the `syn` node they annotate when the file parses. An
+//! attached directive suppresses diagnostics inside exactly that node's
+//! span, including nested items. When the file does not parse, a directive
+//! falls back to covering the ten lines that follow it.
+//!
+//! Crates that cannot define an `omnia::allow` attribute can use the
+//! comment forms `// omnia-lint: allow(...)` (next item) and
+//! `// omnia-lint: allow-line(...)` (same line), or gate the attribute
+//! behind `#[cfg_attr(omnia_lint, omnia::allow(...))]`.
+//!
+//! `expect` directives suppress like `allow`, but the engine reports them
+//! as `unfulfilled_lint_expectation` when nothing inside them fires.
+//!
+//! Besides rule names a directive may carry a `reason = "..."` justifying
+//! the waiver and an `until = "YYYY-MM-DD"` date after which it expires.
+
+use std::collections::HashSet;
+
+use regex::Regex;
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+
+use crate::diagnostics::Diagnostic;
+use crate::rules::RuleCategory;
+use crate::tokens::{RegionKind, TokenMap};
+
+/// Number of lines a directive covers when the file cannot be parsed.
+const FALLBACK_WINDOW: usize = 10;
+
+/// Parsed ignore directive from source code.
+#[derive(Debug, Clone)]
+pub struct IgnoreDirective {
+    /// Line number where the directive appears (1-indexed).
+    pub line: usize,
+    /// Whether this is a file-level directive (#![...]).
+    pub is_file_level: bool,
+    /// How the directive was written, which decides what it covers.
+    pub form: DirectiveForm,
+    /// Whether this is an `expect` directive that must suppress something.
+    pub is_expectation: bool,
+    /// Rule IDs to ignore, or None for all rules.
+    pub rules: Option<HashSet<String>>,
+    /// Justification given with `reason = "..."`.
+    pub reason: Option<String>,
+    /// Expiry date given with `until = "YYYY-MM-DD"`, as written.
+    pub until: Option<String>,
+    /// Source range of the annotated node, once resolved against the AST.
+    pub scope: Option<DirectiveScope>,
+}
+
+/// Syntax a directive was written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirectiveForm {
+    /// `#[omnia::allow(...)]` or `#[cfg_attr(omnia_lint, omnia::allow(...))]`,
+    /// covering the node the attribute is attached to.
+    Attribute,
+    /// `// omnia-lint: allow(...)`, covering the next item or statement.
+    NextItemComment,
+    /// `// omnia-lint: allow-line(...)`, covering its own line.
+    LineComment,
+}
+
+/// Lines covered by a directive (1-indexed, inclusive).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirectiveScope {
+    /// First line of the annotated node, including its attributes.
+    pub start_line: usize,
+    /// Last line of the annotated node.
+    pub end_line: usize,
+}
+
+impl DirectiveScope {
+    /// Check if a line falls within this scope.
+    pub fn contains(&self, line: usize) -> bool {
+        self.start_line <= line && line <= self.end_line
+    }
+
+    /// Number of lines spanned, used to pick the innermost node.
+    fn height(&self) -> usize {
+        self.end_line - self.start_line
+    }
+
+    fn from_span(span: proc_macro2::Span) -> Self {
+        Self {
+            start_line: span.start().line,
+            end_line: span.end().line,
+        }
+    }
+}
+
+impl IgnoreDirective {
+    /// Check if this directive allows (ignores) a specific rule.
+    pub fn allows(&self, rule_id: &str) -> bool {
+        match &self.rules {
+            None => true, // "all" - ignore everything
+            Some(rules) => {
+                rules.contains("all")
+                    || rules.contains(rule_id)
+                    || rules.contains(&rule_id.to_lowercase())
+            }
+        }
+    }
+
+    /// Check if this directive allows a whole rule category (e.g. `error`).
+    pub fn allows_category(&self, category: RuleCategory) -> bool {
+        match &self.rules {
+            None => true,
+            Some(rules) => rules.contains(category.as_key()),
+        }
+    }
+
+    /// Attribute path naming this kind of directive in messages.
+    pub fn attribute_path(&self) -> &'static str {
+        if self.is_expectation { "omnia::expect" } else { "omnia::allow" }
+    }
+
+    /// Check if the `until` date lies before `today` (both `YYYY-MM-DD`).
+    ///
+    /// Directives without a valid `until` date never expire.
+    pub fn is_expired(&self, today: &str) -> bool {
+        self.until.as_deref().is_some_and(|until| is_valid_date(until) && until < today)
+    }
+
+    /// Check if this directive suppresses a diagnostic.
+    pub fn covers(&self, diagnostic: &Diagnostic) -> bool {
+        if !self.allows(&diagnostic.rule_id) && !self.allows_category(diagnostic.category) {
+            return false;
+        }
+
+        match self.scope {
+            Some(scope) => scope.contains(diagnostic.line),
+            // File-level directives apply to everything
+            None if self.is_file_level => true,
+            // Unparsable file: cover the lines following the directive
+            None => self.line < diagnostic.line && diagnostic.line <= self.line + FALLBACK_WINDOW,
+        }
+    }
+}
+
+/// Parse ignore directives from source code.
+///
+/// Supports:
+/// - `#[omnia::allow(all)]` - ignore all rules for the next item
+/// - `#[omnia::allow(rule_id)]` - ignore specific rule for the next item
+/// - `#[omnia::allow(rule1, rule2)]` - ignore multiple rules for the next item
+/// - `#[omnia::allow(category)]` - ignore every rule in a category (e.g. `error`)
+/// - `#[omnia::allow(rule_id, reason = "...", until = "2026-12-31")]` - justified, expiring waiver
+/// - `#![omnia::allow(...)]` - file-level ignore (inner attribute)
+/// - `#[cfg_attr(omnia_lint, omnia::allow(...))]` - same as the plain attribute
+/// - `// omnia-lint: allow(rule_id) -- reason` - ignore rule for the next item
+/// - `// omnia-lint: allow-line(rule_id) -- reason` - ignore rule on this line
+///
+/// Every form also accepts `expect` in place of `allow`
+/// (`#[omnia::expect(rule_id)]`, `// omnia-lint: expect-line(rule_id)`, ...).
+///
+/// Attribute and next-item directives have no scope until
+/// [`resolve_directive_scopes`] attaches them to the AST.
+pub fn parse_ignore_directives(content: &str) -> Vec<IgnoreDirective> {
+    parse_directives(content, &TokenMap::new(content))
+}
+
+/// [`parse_ignore_directives`] with the file already classified.
+pub(crate) fn parse_directives(content: &str, tokens: &TokenMap) -> Vec<IgnoreDirective> {
+    let mut directives = Vec::new();
+
+    // Opening of #[omnia::allow(...)] or #![omnia::allow(...)]
+    let attr_pattern = Regex::new(r#"#(?P<inner>!?)\[omnia::(?P<kind>allow|expect)\("#).unwrap();
+    // Opening of #[cfg_attr(omnia_lint, omnia::allow(...))]
+    let cfg_attr_pattern = Regex::new(
+        r#"#(?P<inner>!?)\[cfg_attr\(\s*omnia_lint\s*,\s*omnia::(?P<kind>allow|expect)\("#,
+    )
+    .unwrap();
+    // Pattern for // omnia-lint: allow(...) -- reason
+    let comment_pattern = Regex::new(
+        r#"//\s*omnia-lint:\s*(?P<kind>allow|expect)(?P<line>-line)?\((?P<args>.+?)\)(?:\s*--\s*(?P<reason>.*\S))?"#,
+    )
+    .unwrap();
+
+    let mut line_start = 0;
+    for (line_idx, raw_line) in content.split_inclusive('\n').enumerate() {
+        let line_number = line_idx + 1;
+        let offset = line_start;
+        line_start += raw_line.len();
+        let line = raw_line.trim_end_matches(['\n', '\r']);
+
+        // Only a real comment is a directive, not the same text in a string
+        let comment = comment_pattern.captures_iter(line).find(|caps| {
+            caps.get(0).is_some_and(|m| tokens.kind_at(offset + m.start()) == RegionKind::Comment)
+        });
+        let (caps, form, args) = if let Some(caps) = comment {
+            let form = if caps.name("line").is_some() {
+                DirectiveForm::LineComment
+            } else {
+                DirectiveForm::NextItemComment
+            };
+            let args = caps.name("args").map_or("", |m| m.as_str());
+            (caps, form, args)
+        } else if let Some((caps, closing)) = attr_pattern
+            .captures_iter(line)
+            .map(|caps| (caps, "]"))
+            .chain(cfg_attr_pattern.captures_iter(line).map(|caps| (caps, ")]")))
+            // Attributes in comments and strings, doc examples included, are not live
+            .find(|(caps, _)| {
+                caps.get(0).is_some_and(|m| tokens.kind_at(offset + m.start()) == RegionKind::Code)
+            })
+        {
+            let start = caps.get(0).map_or(0, |m| m.end());
+            let Some(end) = args_end(line, offset, start, tokens) else { continue };
+            if !closes_with(&line[end + 1..], closing) {
+                continue;
+            }
+            (caps, DirectiveForm::Attribute, &line[start..end])
+        } else {
+            continue;
+        };
+
+        let is_file_level = caps.name("inner").is_some_and(|m| m.as_str() == "!");
+        let is_expectation = caps.name("kind").is_some_and(|m| m.as_str() == "expect");
+        let (rules, mut reason, until) = parse_directive_args(args);
+        if let Some(trailing) = caps.name("reason") {
+            reason.get_or_insert_with(|| trailing.as_str().to_string());
+        }
+
+        let scope = (form == DirectiveForm::LineComment).then_some(DirectiveScope {
+            start_line: line_number,
+            end_line: line_number,
+        });
+
+        directives.push(IgnoreDirective {
+            line: line_number,
+            is_file_level,
+            form,
+            is_expectation,
+            rules,
+            reason,
+            until,
+            scope,
+        });
+    }
+
+    directives
+}
+
+/// Index in `line` of the `)` that closes directive arguments starting at
+/// `start`. Parentheses in string literals, such as a `reason`, do not count.
+///
+/// `offset` is the byte offset of the line in the file `tokens` classifies.
+fn args_end(line: &str, offset: usize, start: usize, tokens: &TokenMap) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in line[start..].char_indices().map(|(i, c)| (start + i, c)) {
+        if tokens.kind_at(offset + i) == RegionKind::String {
+            continue;
+        }
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(i),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Check that `rest` starts with the characters of `closing`, allowing
+/// whitespace between them.
+fn closes_with(rest: &str, closing: &str) -> bool {
+    let mut rest = rest;
+    for c in closing.chars() {
+        match rest.trim_start().strip_prefix(c) {
+            Some(after) => rest = after,
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Split directive arguments into rule names, `reason` and `until`.
+///
+/// A lone `all` yields `None` for the rule names.
+fn parse_directive_args(args: &str) -> (Option<HashSet<String>>, Option<String>, Option<String>) {
+    let mut names = HashSet::new();
+    let mut reason = None;
+    let mut until = None;
+
+    for arg in split_directive_args(args) {
+        match arg.split_once('=') {
+            Some((key, value)) if key.trim() == "reason" => {
+                reason = Some(unquote(value));
+            }
+            Some((key, value)) if key.trim() == "until" => {
+                until = Some(unquote(value));
+            }
+            // Unknown keys are kept as names so they are reported as unknown lints
+            Some((key, _)) => {
+                names.insert(key.trim().to_string());
+            }
+            None => {
+                names.insert(arg.to_string());
+            }
+        }
+    }
+
+    let rules = if names.len() == 1 && names.iter().all(|n| n.to_lowercase() == "all") {
+        None
+    } else {
+        Some(names)
+    };
+    (rules, reason, until)
+}
+
+/// Split directive arguments on commas that are not inside a string literal.
+fn split_directive_args(args: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (i, c) in args.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            ',' if !in_string => {
+                parts.push(&args[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&args[start..]);
+
+    parts.into_iter().map(str::trim).filter(|p| !p.is_empty()).collect()
+}
+
+/// Strip the quotes from a directive string value and resolve `\"` and `\\`.
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    let inner = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value);
+    inner.replace("\\\"", "\"").replace("\\\\", "\\")
+}
+
+/// Check that a string is a calendar date in `YYYY-MM-DD` form.
+pub fn is_valid_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = parts.as_slice() else {
+        return false;
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return false;
+    }
+    let (Ok(year), Ok(month), Ok(day)) =
+        (year.parse::<i64>(), month.parse::<u32>(), day.parse::<u32>())
+    else {
+        return false;
+    };
+
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days_in_month).contains(&day)
+}
+
+/// Today's date (UTC) in `YYYY-MM-DD` form.
+pub fn today() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Convert days since 1970-01-01 to a `(year, month, day)` civil date.
+///
+/// Howard Hinnant's `civil_from_days` algorithm.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Attach each directive to the AST node it annotates.
+///
+/// Attributes cover the node they are attached to. Inner attributes at the
+/// top of the file keep no scope and stay file-wide; inner attributes inside
+/// a module or function cover that item. `// omnia-lint: allow(...)` comments
+/// cover the outermost node starting after them. A directive that cannot be
+/// matched to any node (for example inside a macro body) only covers its own
+/// line, unless `partial` says `file` holds only the items recovered from a
+/// file that does not parse: the directive may then annotate an item that
+/// was dropped, and keeps the fallback window.
+pub fn resolve_directive_scopes(
+    directives: &mut [IgnoreDirective], file: &syn::File, partial: bool,
+) {
+    let mut collector = AttributeScopeCollector::default();
+    collector.visit_file(file);
+
+    let file_level_lines: HashSet<usize> =
+        file.attrs.iter().map(|a| a.span().start().line).collect();
+
+    for directive in directives {
+        let resolved = match directive.form {
+            DirectiveForm::LineComment => continue,
+            DirectiveForm::Attribute
+                if directive.is_file_level && file_level_lines.contains(&directive.line) =>
+            {
+                continue;
+            }
+            DirectiveForm::Attribute => collector
+                .owners
+                .iter()
+                .filter(|(attr_line, _)| *attr_line == directive.line)
+                .map(|(_, scope)| *scope)
+                .min_by_key(DirectiveScope::height),
+            DirectiveForm::NextItemComment => collector
+                .nodes
+                .iter()
+                .filter(|node| node.start_line > directive.line)
+                .min_by_key(|node| (node.start_line, std::cmp::Reverse(node.height())))
+                .copied(),
+        };
+
+        directive.scope = match resolved {
+            Some(scope) => Some(scope),
+            None if partial => None,
+            None => Some(DirectiveScope {
+                start_line: directive.line,
+                end_line: directive.line,
+            }),
+        };
+    }
+}
+
+/// Check if a diagnostic should be ignored based on directives.
+pub(crate) fn should_ignore_diagnostic(
+    diagnostic: &Diagnostic, directives: &[IgnoreDirective],
+) -> bool {
+    directives.iter().any(|d| d.covers(diagnostic))
+}
+
+/// Visitor that records, for every attribute, the span of the node that owns it.
+///
+/// Nodes that can carry attributes push their span before their children
+/// are walked; `syn` visits a node's attributes before its body, so the top
+/// of the stack is always the attribute's owner.
+#[derive(Default)]
+struct AttributeScopeCollector {
+    stack: Vec<DirectiveScope>,
+    /// `(attribute line, owner scope)` pairs.
+    owners: Vec<(usize, DirectiveScope)>,
+    /// Every node that can carry attributes, in visit order.
+    nodes: Vec<DirectiveScope>,
+}
+
+impl AttributeScopeCollector {
+    fn enter(&mut self, span: proc_macro2::Span) {
+        let scope = DirectiveScope::from_span(span);
+        self.nodes.push(scope);
+        self.stack.push(scope);
+    }
+
+    fn exit(&mut self) {
+        self.stack.pop();
+    }
+}
+
+impl<'ast> Visit<'ast> for AttributeScopeCollector {
+    fn visit_attribute(&mut self, attr: &'ast syn::Attribute) {
+        if let Some(owner) = self.stack.last() {
+            self.owners.push((attr.span().start().line, *owner));
+        }
+    }
+
+    fn visit_item(&mut self, node: &'ast syn::Item) {
+        self.enter(node.span());
+        syn::visit::visit_item(self, node);
+        self.exit();
+    }
+
+    fn visit_impl_item(&mut self, node: &'ast syn::ImplItem) {
+        self.enter(node.span());
+        syn::visit::visit_impl_item(self, node);
+        self.exit();
+    }
+
+    fn visit_trait_item(&mut self, node: &'ast syn::TraitItem) {
+        self.enter(node.span());
+        syn::visit::visit_trait_item(self, node);
+        self.exit();
+    }
+
+    fn visit_foreign_item(&mut self, node: &'ast syn::ForeignItem) {
+        self.enter(node.span());
+        syn::visit::visit_foreign_item(self, node);
+        self.exit();
+    }
+
+    fn visit_local(&mut self, node: &'ast syn::Local) {
+        self.enter(node.span());
+        syn::visit::visit_local(self, node);
+        self.exit();
+    }
+
+    fn visit_stmt_macro(&mut self, node: &'ast syn::StmtMacro) {
+        self.enter(node.span());
+        syn::visit::visit_stmt_macro(self, node);
+        self.exit();
+    }
+
+    fn visit_expr(&mut self, node: &'ast syn::Expr) {
+        self.enter(node.span());
+        syn::visit::visit_expr(self, node);
+        self.exit();
+    }
+
+    fn visit_field(&mut self, node: &'ast syn::Field) {
+        self.enter(node.span());
+        syn::visit::visit_field(self, node);
+        self.exit();
+    }
+
+    fn visit_variant(&mut self, node: &'ast syn::Variant) {
+        self.enter(node.span());
+        syn::visit::visit_variant(self, node);
+        self.exit();
+    }
+
+    fn visit_arm(&mut self, node: &'ast syn::Arm) {
+        self.enter(node.span());
+        syn::visit::visit_arm(self, node);
+        self.exit();
+    }
+
+    fn visit_field_value(&mut self, node: &'ast syn::FieldValue) {
+        self.enter(node.span());
+        syn::visit::visit_field_value(self, node);
+        self.exit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolved(content: &str) -> Vec<IgnoreDirective> {
+        let mut directives = parse_ignore_directives(content);
+        let file = syn::parse_file(content).unwrap();
+        resolve_directive_scopes(&mut directives, &file, false);
+        directives
+    }
+
+    #[test]
+    fn test_parse_ignore_directives() {
+        let content = r#"
+#![omnia::allow(all)]
+#[omnia::allow(unwrap_used)]
+fn foo() {}
+#[omnia::allow(rule1, rule2)]
+fn bar() {}
+"#;
+        let directives = parse_ignore_directives(content);
+        assert_eq!(directives.len(), 3);
+
+        // First directive is file-level, ignores all
+        assert!(directives[0].is_file_level);
+        assert!(directives[0].rules.is_none());
+
+        // Second directive is line-level, ignores specific rule
+        assert!(!directives[1].is_file_level);
+        assert!(directives[1].rules.as_ref().unwrap().contains("unwrap_used"));
+
+        // Third directive ignores multiple rules
+        assert!(!directives[2].is_file_level);
+        let rules = directives[2].rules.as_ref().unwrap();
+        assert!(rules.contains("rule1"));
+        assert!(rules.contains("rule2"));
+    }
+
+    #[test]
+    fn test_parse_reason_and_until() {
+        let content = r#"#[omnia::allow(error_generic_unwrap, reason = "input is validated, see (1)", until = "2026-12-31")]"#;
+        let directives = parse_ignore_directives(content);
+        assert_eq!(directives.len(), 1);
+        let directive = &directives[0];
+        assert!(directive.allows("error_generic_unwrap"));
+        assert_eq!(directive.rules.as_ref().unwrap().len(), 1);
+        assert_eq!(directive.reason.as_deref(), Some("input is validated, see (1)"));
+        assert_eq!(directive.until.as_deref(), Some("2026-12-31"));
+    }
+
+    #[test]
+    fn test_parse_reason_with_closing_brackets() {
+        let content = r#"#[omnia::allow(error_generic_unwrap, reason = "see `v[f(x)]` above")] fn f() {}
+#[cfg_attr(omnia_lint, omnia::expect(std_fs, reason = "ok :)]"))]
+#[omnia::allow(std_env"#;
+        let directives = parse_ignore_directives(content);
+
+        assert_eq!(directives.len(), 2);
+        assert_eq!(directives[0].reason.as_deref(), Some("see `v[f(x)]` above"));
+        assert!(directives[0].rules.as_ref().unwrap().contains("error_generic_unwrap"));
+        assert!(directives[1].is_expectation);
+        assert_eq!(directives[1].reason.as_deref(), Some("ok :)]"));
+    }
+
+    #[test]
+    fn test_comment_directives_in_strings_are_ignored() {
+        let content = r#"let s = "// omnia-lint: allow-line(error_generic_unwrap)"; // omnia-lint: allow-line(std_fs)
+let t = "// omnia-lint: allow(all)";
+"#;
+        let directives = parse_ignore_directives(content);
+
+        assert_eq!(directives.len(), 1);
+        assert_eq!(directives[0].line, 1);
+        assert_eq!(directives[0].rules, Some(HashSet::from(["std_fs".to_string()])));
+    }
+
+    #[test]
+    fn test_attribute_directives_in_comments_are_ignored() {
+        let content = r##"/// Example: #[omnia::allow(all)]
+// #[omnia::allow(wasm)]
+/* #[cfg_attr(omnia_lint, omnia::allow(std_fs))] */
+let s = "#[omnia::expect(std_env)]";
+#[omnia::allow(std_net)] // #[omnia::allow(all)]
+fn f() {}
+"##;
+        let directives = parse_ignore_directives(content);
+
+        assert_eq!(directives.len(), 1);
+        assert_eq!(directives[0].line, 5);
+        assert_eq!(directives[0].rules, Some(HashSet::from(["std_net".to_string()])));
+    }
+
+    #[test]
+    fn test_parse_all_with_reason() {
+        let directives = parse_ignore_directives(r#"#[omnia::allow(all, reason = "generated")]"#);
+        assert!(directives[0].rules.is_none());
+        assert_eq!(directives[0].reason.as_deref(), Some("generated"));
+    }
+
+    #[test]
+    fn test_is_expired() {
+        let directives = parse_ignore_directives(r#"#[omnia::allow(all, until = "2026-06-30")]"#);
+        assert!(directives[0].is_expired("2026-07-01"));
+        assert!(!directives[0].is_expired("2026-06-30"));
+    }
+
+    #[test]
+    fn test_is_valid_date() {
+        assert!(is_valid_date("2026-12-31"));
+        assert!(is_valid_date("2024-02-29"));
+        assert!(!is_valid_date("2026-02-29"));
+        assert!(!is_valid_date("2026-13-01"));
+        assert!(!is_valid_date("31/12/2026"));
+        assert!(!is_valid_date("2026-1-01"));
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_723), (2024, 1, 1));
+        assert_eq!(civil_from_days(20_513), (2026, 3, 1));
+        assert!(is_valid_date(&today()));
+    }
+
+    #[test]
+    fn test_parse_comment_directives() {
+        let content = r#"
+// omnia-lint: allow(error_generic_unwrap) -- input is validated upstream
+fn foo() {}
+let x = y.unwrap(); // omnia-lint: allow-line(error_generic_unwrap, until = "2026-12-31")
+#[cfg_attr(omnia_lint, omnia::allow(error_panic_macro, reason = "unreachable"))]
+fn bar() {}
+"#;
+        let directives = parse_ignore_directives(content);
+        assert_eq!(directives.len(), 3);
+
+        assert_eq!(directives[0].form, DirectiveForm::NextItemComment);
+        assert!(directives[0].allows("error_generic_unwrap"));
+        assert_eq!(directives[0].reason.as_deref(), Some("input is validated upstream"));
+
+        assert_eq!(directives[1].form, DirectiveForm::LineComment);
+        assert_eq!(directives[1].until.as_deref(), Some("2026-12-31"));
+        assert_eq!(
+            directives[1].scope,
+            Some(DirectiveScope {
+                start_line: 4,
+                end_line: 4
+            })
+        );
+
+        assert_eq!(directives[2].form, DirectiveForm::Attribute);
+        assert!(directives[2].allows("error_panic_macro"));
+        assert_eq!(directives[2].reason.as_deref(), Some("unreachable"));
+    }
+
+    #[test]
+    fn test_parse_expect_directives() {
+        let content = r#"
+#[omnia::expect(error_generic_unwrap)]
+#[cfg_attr(omnia_lint, omnia::expect(error_panic_macro))]
+// omnia-lint: expect(error_generic_unwrap) -- known issue
+let x = y.unwrap(); // omnia-lint: expect-line(error_generic_unwrap)
+#[omnia::allow(error_generic_unwrap)]
+"#;
+        let directives = parse_ignore_directives(content);
+        let expectations: Vec<bool> = directives.iter().map(|d| d.is_expectation).collect();
+        assert_eq!(expectations, vec![true, true, true, true, false]);
+        assert_eq!(directives[2].reason.as_deref(), Some("known issue"));
+        assert_eq!(directives[3].form, DirectiveForm::LineComment);
+        assert_eq!(directives[0].attribute_path(), "omnia::expect");
+    }
+
+    #[test]
+    fn test_comment_directive_covers_next_item() {
+        let content = r#"
+fn before() {}
+// omnia-lint: allow(error_generic_unwrap)
+#[inline]
+fn target() {
+    let x = Some(5).unwrap();
+}
+fn after() {}
+"#;
+        let directives = resolved(content);
+        let scope = directives[0].scope.unwrap();
+        assert_eq!((scope.start_line, scope.end_line), (4, 7));
+    }
+
+    #[test]
+    fn test_comment_directive_covers_next_statement() {
+        let content = r#"
+fn main() {
+    // omnia-lint: allow(error_generic_unwrap)
+    let x = Some(5)
+        .unwrap();
+    let y = Some(6).unwrap();
+}
+"#;
+        let directives = resolved(content);
+        let scope = directives[0].scope.unwrap();
+        assert_eq!((scope.start_line, scope.end_line), (4, 5));
+    }
+
+    #[test]
+    fn test_cfg_attr_directive_covers_item() {
+        let content = r#"
+#[cfg_attr(omnia_lint, omnia::allow(error_generic_unwrap))]
+fn target() {
+    let x = Some(5).unwrap();
+}
+"#;
+        let directives = resolved(content);
+        let scope = directives[0].scope.unwrap();
+        assert_eq!((scope.start_line, scope.end_line), (2, 5));
+    }
+
+    #[test]
+    fn test_scope_covers_whole_item() {
+        let content = r#"
+#[omnia::allow(error_generic_unwrap)]
+fn long() {
+    let a = 1;
+    let b = 2;
+    let c = 3;
+    let d = 4;
+    let e = 5;
+    let f = 6;
+    let g = 7;
+    let h = 8;
+    let x = Some(5).unwrap();
+}
+"#;
+        let directives = resolved(content);
+        let scope = directives[0].scope.unwrap();
+        assert_eq!((scope.start_line, scope.end_line), (2, 13));
+    }
+
+    #[test]
+    fn test_scope_innermost_statement() {
+        let content = r#"
+fn main() {
+    #[omnia::allow(error_generic_unwrap)]
+    let x = Some(5).unwrap();
+    let y = Some(6).unwrap();
+}
+"#;
+        let directives = resolved(content);
+        let scope = directives[0].scope.unwrap();
+        assert_eq!((scope.start_line, scope.end_line), (3, 4));
+    }
+
+    #[test]
+    fn test_file_level_directive_has_no_scope() {
+        let content = "#![omnia::allow(all)]\nfn main() {}\n";
+        let directives = resolved(content);
+        assert!(directives[0].scope.is_none());
+    }
+
+    #[test]
+    fn test_inner_directive_in_module_covers_module() {
+        let content = r#"
+mod inner {
+    #![omnia::allow(all)]
+    fn a() {}
+}
+fn b() {}
+"#;
+        let directives = resolved(content);
+        let scope = directives[0].scope.unwrap();
+        assert_eq!((scope.start_line, scope.end_line), (2, 5));
+    }
+}
@@ -0,0 +1,325 @@
+//! Classification of source text into code, string and comment regions.
+//!
+//! Regex rules only look at one line at a time, so they need to know
+//! whether a match sits inside a string literal or a comment. The file is
+//! tokenised once with `proc_macro2`; any text between tokens is a comment.
+//! Files that `proc_macro2` rejects (for example unbalanced delimiters) are
+//! classified with a small hand-written lexer instead.
+
+use std::ops::Range;
+
+use proc_macro2::{TokenStream, TokenTree};
+
+/// Kind of a source region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionKind {
+    /// Ordinary code.
+    Code,
+    /// String, byte string, raw string or character literal.
+    String,
+    /// Line, block or doc comment.
+    Comment,
+}
+
+/// Non-code regions of a source file, sorted by byte offset.
+#[derive(Debug, Clone, Default)]
+pub struct TokenMap {
+    regions: Vec<(Range<usize>, RegionKind)>,
+}
+
+impl TokenMap {
+    /// Classify every byte of `content`.
+    pub fn new(content: &str) -> Self {
+        let regions = match content.parse::<TokenStream>() {
+            Ok(stream) => regions_from_tokens(content, stream),
+            Err(_) => regions_from_lexer(content),
+        };
+
+        Self { regions }
+    }
+
+    /// Kind of the region containing a byte offset.
+    pub fn kind_at(&self, offset: usize) -> RegionKind {
+        let idx = self.regions.partition_point(|(range, _)| range.end <= offset);
+        match self.regions.get(idx) {
+            Some((range, kind)) if range.start <= offset => *kind,
+            _ => RegionKind::Code,
+        }
+    }
+
+    /// Check if a byte offset lies in code rather than a string or comment.
+    pub fn is_code(&self, offset: usize) -> bool {
+        self.kind_at(offset) == RegionKind::Code
+    }
+}
+
+/// Build regions from a token stream; gaps between tokens are comments.
+fn regions_from_tokens(content: &str, stream: TokenStream) -> Vec<(Range<usize>, RegionKind)> {
+    let mut leaves = Vec::new();
+    collect_leaves(content, stream, &mut leaves);
+    // Doc comments expand to several tokens sharing one span; keep the widest
+    leaves.sort_by_key(|(range, _)| (range.start, std::cmp::Reverse(range.end)));
+
+    let mut regions = Vec::new();
+    let mut cursor = 0;
+    for (range, kind) in leaves {
+        if range.start < cursor {
+            continue;
+        }
+        push_gap(content, cursor..range.start, &mut regions);
+        if kind != RegionKind::Code {
+            regions.push((range.clone(), kind));
+        }
+        cursor = range.end;
+    }
+    push_gap(content, cursor..content.len(), &mut regions);
+
+    regions
+}
+
+/// Record the byte range and kind of every token, including group delimiters.
+fn collect_leaves(
+    content: &str, stream: TokenStream, leaves: &mut Vec<(Range<usize>, RegionKind)>,
+) {
+    for tree in stream {
+        match tree {
+            TokenTree::Group(group) => {
+                leaves.push((group.span_open().byte_range(), RegionKind::Code));
+                collect_leaves(content, group.stream(), leaves);
+                leaves.push((group.span_close().byte_range(), RegionKind::Code));
+            }
+            TokenTree::Literal(literal) => {
+                let range = literal.span().byte_range();
+                let kind = leaf_kind(&content[range.clone()], true);
+                leaves.push((range, kind));
+            }
+            other => {
+                let range = other.span().byte_range();
+                let kind = leaf_kind(&content[range.clone()], false);
+                leaves.push((range, kind));
+            }
+        }
+    }
+}
+
+/// Classify a token by its source text. Doc comments surface as tokens.
+fn leaf_kind(text: &str, is_literal: bool) -> RegionKind {
+    if text.starts_with("//") || text.starts_with("/*") {
+        RegionKind::Comment
+    } else if is_literal && is_text_literal(text) {
+        RegionKind::String
+    } else {
+        RegionKind::Code
+    }
+}
+
+/// Check if literal source text is a string, byte string, C string or char.
+fn is_text_literal(text: &str) -> bool {
+    text.trim_start_matches(['b', 'c', 'r', '#']).starts_with(['"', '\''])
+}
+
+/// Mark the non-whitespace part of the text between two tokens as comment.
+fn push_gap(content: &str, gap: Range<usize>, regions: &mut Vec<(Range<usize>, RegionKind)>) {
+    let text = &content[gap.clone()];
+    let trimmed = text.trim_start();
+    if trimmed.is_empty() {
+        return;
+    }
+    let start = gap.start + (text.len() - trimmed.len());
+    let end = gap.start + text.trim_end().len();
+    regions.push((start..end, RegionKind::Comment));
+}
+
+/// Classify source with a hand-written lexer, for files that do not tokenise.
+fn regions_from_lexer(content: &str) -> Vec<(Range<usize>, RegionKind)> {
+    let bytes = content.as_bytes();
+    let mut regions = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+        let after_ident = i > 0 && is_ident_byte(bytes[i - 1]);
+
+        let end = if rest.starts_with(b"//") {
+            let end = rest.iter().position(|&b| b == b'\n').map_or(bytes.len(), |p| i + p);
+            regions.push((i..end, RegionKind::Comment));
+            end
+        } else if rest.starts_with(b"/*") {
+            let end = block_comment_end(bytes, i);
+            regions.push((i..end, RegionKind::Comment));
+            end
+        } else if let Some(end) = (!after_ident).then(|| text_literal_end(bytes, i)).flatten() {
+            regions.push((i..end, RegionKind::String));
+            end
+        } else {
+            i + 1
+        };
+        i = end;
+    }
+
+    regions
+}
+
+/// End of a (possibly nested) block comment starting at `start`.
+fn block_comment_end(bytes: &[u8], start: usize) -> usize {
+    let mut depth = 0;
+    let mut i = start;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"/*") {
+            depth += 1;
+            i += 2;
+        } else if bytes[i..].starts_with(b"*/") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                return i;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    bytes.len()
+}
+
+/// End of a string or char literal starting at `start`, if one starts there.
+///
+/// Handles `b`, `c` and `r` prefixes, raw string hashes and escapes, and
+/// tells character literals apart from lifetimes.
+fn text_literal_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut i = start;
+    if matches!(bytes.get(i), Some(b'b' | b'c')) {
+        i += 1;
+    }
+
+    if bytes.get(i) == Some(&b'r') {
+        let hashes = bytes[i + 1..].iter().take_while(|&&b| b == b'#').count();
+        let open = i + 1 + hashes;
+        if bytes.get(open) != Some(&b'"') {
+            return None;
+        }
+        let closing: Vec<u8> =
+            std::iter::once(b'"').chain(std::iter::repeat_n(b'#', hashes)).collect();
+        let body = open + 1;
+        return Some(
+            bytes[body..]
+                .windows(closing.len())
+                .position(|w| w == closing.as_slice())
+                .map_or(bytes.len(), |p| body + p + closing.len()),
+        );
+    }
+
+    match bytes.get(i) {
+        Some(b'"') => Some(quoted_end(bytes, i + 1, b'"')),
+        Some(b'\'') => {
+            // 'x' and '\n' are chars; 'a without a closing quote is a lifetime
+            let next = i + 1;
+            if bytes.get(next) == Some(&b'\\') {
+                return Some(quoted_end(bytes, next, b'\''));
+            }
+            let char_len = bytes.get(next).map_or(1, |&b| utf8_len(b));
+            (bytes.get(next + char_len) == Some(&b'\'')).then_some(next + char_len + 1)
+        }
+        _ => None,
+    }
+}
+
+/// Index just past the closing `quote`, honouring backslash escapes.
+fn quoted_end(bytes: &[u8], mut i: usize, quote: u8) -> usize {
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b if b == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+fn utf8_len(first: u8) -> usize {
+    match first {
+        0xF0.. => 4,
+        0xE0.. => 3,
+        0xC0.. => 2,
+        _ => 1,
+    }
+}
+
+fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Classify `needle` (its first occurrence after `skip` earlier ones) with both paths.
+    fn kinds(content: &str, needle: &str, skip: usize) -> (RegionKind, RegionKind) {
+        let offset = content.match_indices(needle).nth(skip).unwrap().0;
+        let tokens = TokenMap::new(content).kind_at(offset);
+        let lexer = TokenMap {
+            regions: regions_from_lexer(content),
+        }
+        .kind_at(offset);
+        (tokens, lexer)
+    }
+
+    #[test]
+    fn test_regular_string() {
+        let content = r#"let x = "hello.unwrap()"; let y = foo.unwrap();"#;
+        assert_eq!(kinds(content, "unwrap", 0), (RegionKind::String, RegionKind::String));
+        assert_eq!(kinds(content, "unwrap", 1), (RegionKind::Code, RegionKind::Code));
+    }
+
+    #[test]
+    fn test_raw_and_byte_strings() {
+        let content = r###"let a = r#"x.unwrap()"#; let b = br"y.unwrap()"; let c = b"z.unwrap()"; d.unwrap();"###;
+        for skip in 0..3 {
+            assert_eq!(kinds(content, "unwrap", skip), (RegionKind::String, RegionKind::String));
+        }
+        assert_eq!(kinds(content, "unwrap", 3), (RegionKind::Code, RegionKind::Code));
+    }
+
+    #[test]
+    fn test_char_literal_quote() {
+        let content = r#"let q = '"'; let y = foo.unwrap(); let z = '"';"#;
+        assert_eq!(kinds(content, "unwrap", 0), (RegionKind::Code, RegionKind::Code));
+    }
+
+    #[test]
+    fn test_lifetimes_are_code() {
+        let content = "fn f<'a>(x: &'a str) -> &'a str { x.unwrap() }";
+        assert_eq!(kinds(content, "unwrap", 0), (RegionKind::Code, RegionKind::Code));
+    }
+
+    #[test]
+    fn test_comments() {
+        let content = "// let x = foo.unwrap();\nlet y = bar.unwrap();";
+        assert_eq!(kinds(content, "unwrap", 0), (RegionKind::Comment, RegionKind::Comment));
+        assert_eq!(kinds(content, "unwrap", 1), (RegionKind::Code, RegionKind::Code));
+    }
+
+    #[test]
+    fn test_nested_block_comment() {
+        let content = "/* outer /* inner */ still.unwrap() */ code.unwrap();";
+        assert_eq!(kinds(content, "unwrap", 0), (RegionKind::Comment, RegionKind::Comment));
+        assert_eq!(kinds(content, "unwrap", 1), (RegionKind::Code, RegionKind::Code));
+    }
+
+    #[test]
+    fn test_doc_comments() {
+        let content = "/// Calls foo.unwrap()\n//! inner.unwrap()\nfn f() { x.unwrap() }";
+        assert_eq!(kinds(content, "unwrap", 0), (RegionKind::Comment, RegionKind::Comment));
+        assert_eq!(kinds(content, "unwrap", 1), (RegionKind::Comment, RegionKind::Comment));
+        assert_eq!(kinds(content, "unwrap", 2), (RegionKind::Code, RegionKind::Code));
+    }
+
+    #[test]
+    fn test_unbalanced_file_uses_lexer() {
+        let content = "fn f( { let s = \"a.unwrap()\"; b.unwrap();";
+        let tokens = TokenMap::new(content);
+        let first = content.find("unwrap").unwrap();
+        let second = content.rfind("unwrap").unwrap();
+        assert_eq!(tokens.kind_at(first), RegionKind::String);
+        assert!(tokens.is_code(second));
+    }
+}